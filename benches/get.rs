@@ -0,0 +1,18 @@
+//! Throughput of the cache hit path.
+//!
+//! A hit on a warm cache is a clock read, a comparison and a reference return so [Object::get()] is
+//! expected to take in order of tens of nanoseconds, mostly spent reading the system time.
+//! A result in order of microseconds is a regression.
+use std::hint::black_box;
+use std::time::Duration;
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use generic_cache::Object;
+
+fn warm_get(c: &mut Criterion) {
+    let cached = Object::new(Duration::from_secs(3600), 100u64, async || {Ok::<u64, ()>(200)});
+    c.bench_function("get on warm cache", |b| b.iter(|| *black_box(&cached).get().unwrap()));
+}
+
+criterion_group!(benches, warm_get);
+criterion_main!(benches);
@@ -0,0 +1,53 @@
+//! Source of current time used by every expiry check of this crate.
+//!
+//! Every [SystemTime] given to a cache, e.g. to [crate::Object::force_expire_at()] or returned by the
+//! schedule of [crate::Object::with_schedule()], is compared against [now()]. Without `tokio-clock` feature,
+//! it is [SystemTime::now()]. With `tokio-clock` feature, it is the wall clock shifted by how far the tokio
+//! clock of the current runtime is from the real time, so a deadline should be built from [now()] rather
+//! than [SystemTime::now()] when tokio time may be paused or advanced.
+use std::time::{Duration, SystemTime};
+
+/// Current time as seen by the caches. It is the system time unless `tokio-clock` feature is enabled.
+#[cfg(not(feature = "tokio-clock"))]
+pub fn now() -> SystemTime {
+    SystemTime::now()
+}
+/// Current time as seen by the caches. It is driven by [tokio::time::Instant] of the current runtime so
+/// that [tokio::time::pause] and [tokio::time::advance] also move the time seen by the caches.
+/// The offset between the tokio clock and the real time is taken on every call so each runtime,
+/// paused or not, see its own clock. Outside of a runtime, it is the system time.
+#[cfg(feature = "tokio-clock")]
+pub fn now() -> SystemTime {
+    let mocked = tokio::time::Instant::now().into_std();
+    let real = std::time::Instant::now();
+    let system = SystemTime::now();
+    match mocked.checked_duration_since(real) {
+        Some(ahead) => system + ahead,
+        None => system - real.duration_since(mocked)
+    }
+}
+/// Time passed since `since`. It is zero if `since` is in the future.
+pub(crate) fn elapsed(since: SystemTime) -> Duration {
+    now().duration_since(since).unwrap_or_default()
+}
+#[cfg(all(test, feature = "tokio-clock"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clock_of_current_runtime() {
+        let paused = tokio::runtime::Builder::new_current_thread().enable_time().start_paused(true).build().unwrap();
+        paused.block_on(async {
+            let start = now();
+            tokio::time::advance(Duration::from_secs(3600)).await;
+            let advanced = now().duration_since(start).unwrap();
+            assert!(advanced > Duration::from_secs(3599), "Expect advancing tokio clock to move the time but got {:?}", advanced);
+        });
+        drop(paused);
+        let real = tokio::runtime::Builder::new_current_thread().enable_time().build().unwrap();
+        real.block_on(async {
+            let drift = now().duration_since(SystemTime::now()).unwrap_or_default();
+            assert!(drift < Duration::from_secs(1), "Expect another runtime to not see the advanced clock but it is {:?} ahead", drift);
+        });
+    }
+}
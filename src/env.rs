@@ -0,0 +1,85 @@
+//! Read the `ttl` of a cache from an environment variable so it can be tuned without code change.
+use std::fmt::{Debug, Display, Formatter};
+use std::time::Duration;
+
+use crate::Object;
+
+/// The environment variable given to [ttl_from_env()] doesn't hold a valid duration.
+pub struct InvalidTtl {
+    var: String,
+    value: String,
+    reason: String
+}
+impl InvalidTtl {
+    /// Name of the environment variable.
+    pub fn var(&self) -> &str {
+        &self.var
+    }
+    /// Value of the environment variable that couldn't be parsed.
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+}
+impl Display for InvalidTtl {
+    fn fmt(&self, fmt: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        write!(fmt, "The environment variable {} = {:?} isn't a valid duration, e.g. 30s or 5m: {}", self.var, self.value, self.reason)
+    }
+}
+impl Debug for InvalidTtl {
+    fn fmt(&self, fmt: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        write!(fmt, "InvalidTtl({:?}, {:?}, {:?})", self.var, self.value, self.reason)
+    }
+}
+
+/// Read a duration from environment variable `var`, e.g. `30s`, `5m` or `1h 30m`, as parsed by
+/// [humantime::parse_duration()]. Return `default` if `var` isn't set.
+///
+/// Require `humantime` feature.
+/// ```rust
+/// use std::time::Duration;
+/// use generic_cache::ttl_from_env;
+///
+/// assert_eq!(ttl_from_env("GENERIC_CACHE_DOC_UNSET_TTL", Duration::from_secs(30)).unwrap(), Duration::from_secs(30));
+/// ```
+pub fn ttl_from_env(var: &str, default: Duration) -> Result<Duration, InvalidTtl> {
+    ttl_from_lookup(var, default, |var| std::env::var_os(var).map(|value| value.to_string_lossy().into_owned()))
+}
+/// Same as [ttl_from_env()] but the value of `var` is read by `lookup` so the parsing can be tested
+/// without modifying the environment of the process.
+fn ttl_from_lookup(var: &str, default: Duration, lookup: impl FnOnce(&str) -> Option<String>) -> Result<Duration, InvalidTtl> {
+    let Some(value) = lookup(var) else {
+        return Ok(default)
+    };
+    humantime::parse_duration(value.trim()).map_err(|e| InvalidTtl {
+        var: var.to_owned(),
+        value,
+        reason: e.to_string()
+    })
+}
+
+/// Require `humantime` feature.
+impl<T, F, E> Object<T, F, E> where F: AsyncFnMut() -> Result<T, E> {
+    /// Same as [Object::new()] but `ttl` is read from environment variable `var` by [ttl_from_env()].
+    /// `default_ttl` is used if `var` isn't set. An invalid value is an error rather than silently
+    /// falling back to `default_ttl` so a typo is noticed.
+    pub fn new_from_env(var: &str, default_ttl: Duration, obj: T, refresh_fn: F) -> Result<Object<T, F, E>, InvalidTtl> {
+        Ok(Object::new(ttl_from_env(var, default_ttl)?, obj, refresh_fn))
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_env_ttl() {
+        let lookup = |value: &'static str| move |_: &str| Some(value.to_owned());
+        let ttl = ttl_from_lookup("CACHE_TTL", Duration::from_secs(1), lookup(" 5m ")).unwrap();
+        assert_eq!(ttl, Duration::from_secs(300), "Expect {:?} to equals {:?}", ttl, Duration::from_secs(300));
+        let default = ttl_from_lookup("CACHE_TTL", Duration::from_secs(1), |_| None).unwrap();
+        assert_eq!(default, Duration::from_secs(1), "Expect default ttl when the variable isn't set");
+        let invalid = ttl_from_lookup("CACHE_TTL", Duration::from_secs(1), lookup("5 parsecs")).unwrap_err();
+        assert_eq!((invalid.var(), invalid.value()), ("CACHE_TTL", "5 parsecs"), "Expect invalid value to be reported");
+        let cached = Object::new_from_env("GENERIC_CACHE_TEST_UNSET_TTL", Duration::from_secs(1), 100, async || {Ok::<u16, ()>(200)}).unwrap();
+        assert_eq!(cached.ttl(), Duration::from_secs(1), "Expect default ttl from an unset variable");
+    }
+}
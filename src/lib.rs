@@ -8,6 +8,26 @@
 //! need to handle [TimeoutError] when cache is expired.
 //! Both usage options still need to handle `refresh_fn` error if any.
 //! 
+//! When the cache need to be shared between threads or tasks, use [SharedCache] instead.
+//! 
+//! # Features
+//! No feature is enabled by default so the core cache has no dependency.
+//! Each integration is behind its own feature:
+//! - `tokio`: Methods that need tokio runtime, e.g. timeout, blocking refresh, background refresh, `AutoRefresh` and `WatchedCache`.
+//! - `tokio-clock`: Read time from tokio clock, see below. It enable `tokio`.
+//! - `bytes`: Cheap clone of [bytes::Bytes](https://docs.rs/bytes) value.
+//! - `stream`: Turn a cache into an endless stream of refreshed value. It enable `tokio`.
+//! - `serde`: Serialize [CacheStats] and [Observation].
+//! - `humantime`: Read `ttl` from an environment variable holding a human duration, e.g. `30s` or `5m`.
+//! 
+//! # Testing with tokio paused time
+//! By default, expiry is checked against the system time. With `tokio-clock` feature, the time is read
+//! from `tokio::time::Instant` instead so a test can use `tokio::time::pause` and `tokio::time::advance`,
+//! which need tokio `test-util` feature, to expire cached values without actually waiting.
+//! A [SystemTime] given to a cache, e.g. to [Object::force_expire_at()], is compared against [clock::now()]
+//! so it should be built from [clock::now()] instead of [SystemTime::now()] when tokio time is paused.
+//! To cache many values by key, use [CacheMap].
+//! 
 //! # Example
 //! - Verify two cached call to get value back to back to check if it is actually the same value.
 //! ```rust
@@ -67,8 +87,43 @@
 //! ```
 use std::fmt::{Debug, Display, Formatter};
 use std::future::Future;
+use std::hash::{BuildHasher, Hash, Hasher, RandomState};
+use std::ops::Deref;
 use std::pin::Pin;
+use std::sync::Arc;
+use std::task::Poll;
 use std::time::{Duration, SystemTime};
+
+pub mod clock;
+#[cfg(feature = "humantime")]
+mod env;
+mod local;
+mod map;
+mod memo;
+mod per_thread;
+mod shared;
+mod shared_map;
+mod sliding;
+#[cfg(feature = "stream")]
+mod stream;
+mod warmup;
+#[cfg(feature = "tokio")]
+mod watch;
+
+#[cfg(feature = "humantime")]
+pub use env::{ttl_from_env, InvalidTtl};
+pub use local::LocalCache;
+pub use map::{CacheEntry, CacheMap, OccupiedEntry, VacantEntry};
+pub use memo::{cache_or_compute, Memo};
+pub use per_thread::ThreadLocalCache;
+pub use shared::SharedCache;
+pub use shared_map::SharedCacheMap;
+pub use sliding::Sliding;
+#[cfg(feature = "tokio")]
+pub use shared::AutoRefresh;
+pub use warmup::warmup_concurrent;
+#[cfg(feature = "tokio")]
+pub use watch::WatchedCache;
 /// The cache is timeout. [Object::refresh()] need to be called.
 #[derive(Clone, Copy)]
 pub struct TimeoutError;
@@ -83,20 +138,423 @@ impl Debug for TimeoutError {
     }
 }
 
+/// The cache has no value yet, e.g. before the first load of [Object::new_lazy()]. See [Object::try_get()] and [Object::try_into_parts()].
+#[derive(Clone, Copy)]
+pub struct NotLoaded;
+impl Display for NotLoaded {
+    fn fmt(&self, fmt: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        write!(fmt, "The cached object has no value loaded yet.")
+    }
+}
+impl Debug for NotLoaded {
+    fn fmt(&self, fmt: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        write!(fmt, "The cached object has no value loaded yet.")
+    }
+}
+
+/// Error of a refresh that can fail for other reason than `refresh_fn` error.
+/// More variant may be added later so a `match` on it need a wildcard arm.
+#[non_exhaustive]
+pub enum RefreshError<E> {
+    /// The refresh didn't complete within the given duration.
+    Timeout(Duration),
+    /// The `refresh_fn` returned an error.
+    Refresh(E),
+    /// The `refresh_fn` panicked. It contain the panic message if it is a string.
+    Panicked(String),
+    /// Another refresh is still in progress so this refresh wasn't started.
+    AlreadyRefreshing
+}
+impl<E> Display for RefreshError<E> where E: Display {
+    fn fmt(&self, fmt: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        match self {
+            RefreshError::Timeout(timeout) => write!(fmt, "The refresh didn't complete within {} ms.", timeout.as_millis()),
+            RefreshError::Refresh(e) => write!(fmt, "The refresh failed: {}", e),
+            RefreshError::Panicked(message) => write!(fmt, "The refresh panicked: {}", message),
+            RefreshError::AlreadyRefreshing => write!(fmt, "Another refresh is already in progress.")
+        }
+    }
+}
+impl<E> Debug for RefreshError<E> where E: Debug {
+    fn fmt(&self, fmt: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        match self {
+            RefreshError::Timeout(timeout) => write!(fmt, "Timeout({:?})", timeout),
+            RefreshError::Refresh(e) => write!(fmt, "Refresh({:?})", e),
+            RefreshError::Panicked(message) => write!(fmt, "Panicked({:?})", message),
+            RefreshError::AlreadyRefreshing => write!(fmt, "AlreadyRefreshing")
+        }
+    }
+}
+
+/// Every outcome of a cache operation that isn't a value, in one type.
+/// [TimeoutError], [NotLoaded] and [RefreshError] can be converted into it with `?` so a function that use many
+/// kind of cache operation can return a single error type.
+/// More variant may be added later so a `match` on it need a wildcard arm.
+#[non_exhaustive]
+pub enum CacheError<E = ()> {
+    /// The cached value is expired. See [TimeoutError].
+    Expired,
+    /// There's no value yet. See [NotLoaded].
+    NotLoaded,
+    /// The `refresh_fn` returned an error.
+    Refresh(E),
+    /// The refresh didn't complete within the given duration.
+    Timeout(Duration),
+    /// The `refresh_fn` panicked. It contain the panic message if it is a string.
+    Panicked(String),
+    /// Another refresh is still in progress so this refresh wasn't started.
+    AlreadyRefreshing
+}
+impl<E> From<TimeoutError> for CacheError<E> {
+    fn from(_: TimeoutError) -> Self {
+        CacheError::Expired
+    }
+}
+impl<E> From<NotLoaded> for CacheError<E> {
+    fn from(_: NotLoaded) -> Self {
+        CacheError::NotLoaded
+    }
+}
+impl<E> From<RefreshError<E>> for CacheError<E> {
+    fn from(e: RefreshError<E>) -> Self {
+        match e {
+            RefreshError::Timeout(timeout) => CacheError::Timeout(timeout),
+            RefreshError::Refresh(e) => CacheError::Refresh(e),
+            RefreshError::Panicked(message) => CacheError::Panicked(message),
+            RefreshError::AlreadyRefreshing => CacheError::AlreadyRefreshing
+        }
+    }
+}
+impl<E> Display for CacheError<E> where E: Display {
+    fn fmt(&self, fmt: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        match self {
+            CacheError::Expired => Display::fmt(&TimeoutError, fmt),
+            CacheError::NotLoaded => Display::fmt(&NotLoaded, fmt),
+            CacheError::Refresh(e) => write!(fmt, "The refresh failed: {}", e),
+            CacheError::Timeout(timeout) => write!(fmt, "The refresh didn't complete within {} ms.", timeout.as_millis()),
+            CacheError::Panicked(message) => write!(fmt, "The refresh panicked: {}", message),
+            CacheError::AlreadyRefreshing => write!(fmt, "Another refresh is already in progress.")
+        }
+    }
+}
+impl<E> Debug for CacheError<E> where E: Debug {
+    fn fmt(&self, fmt: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        match self {
+            CacheError::Expired => write!(fmt, "Expired"),
+            CacheError::NotLoaded => write!(fmt, "NotLoaded"),
+            CacheError::Refresh(e) => write!(fmt, "Refresh({:?})", e),
+            CacheError::Timeout(timeout) => write!(fmt, "Timeout({:?})", timeout),
+            CacheError::Panicked(message) => write!(fmt, "Panicked({:?})", message),
+            CacheError::AlreadyRefreshing => write!(fmt, "AlreadyRefreshing")
+        }
+    }
+}
+
+/// Summary of the current status of a cached object. See [Object::state()].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CacheState {
+    /// The cached value is valid for `remaining` more time.
+    Valid {
+        remaining: Duration
+    },
+    /// The cached value is past the soft `ttl` of [Object::new_tiered()] but it is still served for
+    /// `remaining` more time until the hard `ttl`. It should be refreshed in the background.
+    Stale {
+        age: Duration,
+        remaining: Duration
+    },
+    /// The cached value has expired. `age` is the time since it was last updated.
+    Expired {
+        age: Duration
+    },
+    /// There's no value cached yet or the cached value was evicted.
+    NeverLoaded
+}
+
+/// What to do when refresh fail in [Object::get_or_refresh_or_stale()].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ErrorAction {
+    /// Return the stale value instead of the error.
+    KeepStale,
+    /// Evict the stale value then return the error. Evicted value is never returned again.
+    Evict,
+    /// Keep the stale value in cache but return the error.
+    Propagate
+}
+
+/// Why `refresh_fn` was called. See [Object::last_refresh_reason()].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RefreshReason {
+    /// A refresh method, e.g. [Object::refresh()], was called directly.
+    Manual,
+    /// The `ttl` has elapsed.
+    Expired,
+    /// The value was invalidated by [Object::invalidate()].
+    Invalidated,
+    /// The validator given to [Object::get_or_refresh_validated()] rejected the cached value.
+    ValidatorRejected,
+    /// There was no usable value, e.g. it was evicted.
+    NotLoaded
+}
+
+/// A cached value that is borrowed from the cache when possible or owned when it is returned across
+/// a lock, e.g. from [SharedCache]. It dereference to `T` in both cases so caller doesn't need to care.
+#[derive(Clone, Debug)]
+pub enum CacheValue<'a, T> {
+    /// Borrowed directly from the cache, e.g. from [Object::get_or_refresh_value()].
+    Borrowed(&'a T),
+    /// Owned handle which can outlive the cache, e.g. from [SharedCache::get_or_refresh_value()].
+    Owned(Arc<T>)
+}
+impl<T> Deref for CacheValue<'_, T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        match self {
+            CacheValue::Borrowed(obj) => obj,
+            CacheValue::Owned(obj) => obj
+        }
+    }
+}
+impl<T> CacheValue<'_, T> {
+    /// Convert into an owned [Arc]. The borrowed value is cloned while the owned one is returned as is.
+    pub fn into_arc(self) -> Arc<T> where T: Clone {
+        match self {
+            CacheValue::Borrowed(obj) => Arc::new(obj.clone()),
+            CacheValue::Owned(obj) => obj
+        }
+    }
+}
+
+/// Value cached by [Object::new_with_metadata()] along with the metadata returned by the refresh that
+/// loaded it. The metadata is kept in its own field, see [Object::metadata()], and it dereference to the value
+/// so it can be used as `T`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct WithMetadata<T, M> {
+    value: T,
+    metadata: Option<M>
+}
+impl<T, M> Deref for WithMetadata<T, M> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        &self.value
+    }
+}
+impl<T, M> WithMetadata<T, M> {
+    /// The cached value.
+    pub fn value(&self) -> &T {
+        &self.value
+    }
+    /// Metadata returned along with the value or `None` if the value wasn't loaded by a refresh.
+    pub fn metadata(&self) -> Option<&M> {
+        self.metadata.as_ref()
+    }
+    /// Take the value and drop the metadata.
+    pub fn into_value(self) -> T {
+        self.value
+    }
+}
+
+/// What to do when `refresh_fn` is about to be called sooner than allowed by [Object::with_min_refresh_interval()].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RateLimitAction {
+    /// Skip the call and keep the stale value. The refresh return `Ok` without updating anything.
+    /// When there's no value to serve, e.g. before the first load of [Object::new_lazy()] or after an
+    /// eviction, the call isn't skipped so a placeholder is never returned as a loaded value.
+    ServeStale,
+    /// Wait until the interval elapsed then call `refresh_fn`. Require `tokio` feature.
+    #[cfg(feature = "tokio")]
+    Wait
+}
+
+/// Counters of a cached object, see [Object::stats()].
+/// Only the `get_or_refresh` family count hit and miss because [Object::get()] only borrow the cache.
+/// 
+/// With `serde` feature, it implement `serde::Serialize`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct CacheStats {
+    /// Number of lookup served from cache without refresh.
+    pub hits: u64,
+    /// Number of lookup that needed a refresh.
+    pub misses: u64,
+    /// Number of time `refresh_fn` was called, including the failed one.
+    pub refreshes: u64,
+    /// Number of time `refresh_fn` returned an error.
+    pub errors: u64,
+    /// Number of refreshed value rejected by the validator of [Object::refresh_validated()].
+    pub rejected_refreshes: u64
+}
+impl CacheStats {
+    /// Export the counters in Prometheus text format. Every metric name start with `prefix`,
+    /// e.g. `prefix_hits_total`, so it can be served on a `/metrics` endpoint as is.
+    pub fn to_prometheus(&self, prefix: &str) -> String {
+        let metrics = [
+            ("hits", self.hits, "Lookups served from cache"),
+            ("misses", self.misses, "Lookups that needed a refresh"),
+            ("refreshes", self.refreshes, "Calls to refresh_fn"),
+            ("errors", self.errors, "Failed calls to refresh_fn"),
+            ("rejected_refreshes", self.rejected_refreshes, "Refreshed values rejected by validator")
+        ];
+        let mut text = String::new();
+        for (name, value, help) in metrics {
+            text.push_str(&format!("# HELP {prefix}_{name}_total {help}\n# TYPE {prefix}_{name}_total counter\n{prefix}_{name}_total {value}\n"));
+        }
+        text
+    }
+}
+
+/// Snapshot of a cached object taken at a single point in time, see [Object::observe()].
+/// It is owned so a metrics scraper can release the lock guarding the cache right after taking it.
+///
+/// With `serde` feature, it implement `serde::Serialize`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Observation {
+    /// Time since the value was last updated.
+    pub age: Duration,
+    /// Time remain before the value need a refresh. It is zero once `is_expired` is `true`.
+    pub remaining_ttl: Duration,
+    /// Whether the next `get_or_refresh` call would refresh the value.
+    pub is_expired: bool,
+    /// Whether the cache hold a real value, see [Object::is_loaded()].
+    pub is_loaded: bool,
+    /// Counters of the cache, see [Object::stats()].
+    pub stats: CacheStats
+}
+
+/// How [Object::refresh_with_policy()] retry a failed refresh.
+/// The delay before retry `n` is `base * 2^n` capped at `max_delay`, then reduced by a random fraction
+/// of up to `jitter` so many callers don't retry at the same time.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RetryPolicy {
+    /// Maximum number of `refresh_fn` call, including the first one.
+    pub max_attempts: u32,
+    /// Delay before the first retry.
+    pub base: Duration,
+    /// Upper bound of the delay before any retry.
+    pub max_delay: Duration,
+    /// Fraction of the delay between 0.0 and 1.0 that may be randomly removed. 0.0 mean no jitter.
+    pub jitter: f64
+}
+impl RetryPolicy {
+    /// Delay before retry `retry` without jitter. The first retry is 0.
+    pub fn backoff(&self, retry: u32) -> Duration {
+        self.base.saturating_mul(2u32.saturating_pow(retry)).min(self.max_delay)
+    }
+    /// Delay before retry `retry` with random jitter applied.
+    fn delay(&self, retry: u32) -> Duration {
+        self.backoff(retry).mul_f64(1.0 - self.jitter.clamp(0.0, 1.0) * random_fraction())
+    }
+}
+/// Random number between 0.0 and 1.0 without depending on a random number crate.
+fn random_fraction() -> f64 {
+    RandomState::new().build_hasher().finish() as f64 / u64::MAX as f64
+}
+
+/// One of the refresh functions given to [Object::with_fallback_chain()]. Each source may be a different
+/// function so it is boxed and it return a boxed future, like [CachedObject] does.
+pub type RefreshSource<'a, T, E> = Box<dyn FnMut() -> Pin<Box<dyn Future<Output = Result<T, E>> + 'a>> + 'a>;
+
+/// Function that compute when a value expire from its update time, see [Object::with_schedule()].
+type Schedule = fn(SystemTime) -> SystemTime;
+
+/// Async transform applied to every refreshed value, see [Object::set_post_transform()].
+type PostTransform<T, E> = Arc<dyn Fn(T) -> Pin<Box<dyn Future<Output = Result<T, E>> + Send>> + Send + Sync>;
+
+/// Apply `transform`, if any, to a value returned by `refresh_fn`.
+async fn apply_post_transform<T, E>(transform: Option<&PostTransform<T, E>>, obj: T) -> Result<T, E> {
+    match transform {
+        Some(transform) => transform(obj).await,
+        None => Ok(obj)
+    }
+}
+
 /// Generic cache object which cache an object for given period of time before it return TimeoutError
 /// to signal caller to call refresh function before further attempt.
 /// The refresh_fn should be async function that return Result of the same type as the cached object.
 /// If there's any error occur inside refresh_fn, it should return Error result back.
-#[derive(Clone, Copy)]
-pub struct Object<T, F, E = ()> where F: AsyncFnMut() -> Result<T, E> {
+/// 
+/// The `AsyncFnMut` bound is stable since Rust 1.85 and it is also implemented for every `FnMut`
+/// that return a `Future`. A plain `fn` returning `impl Future` or an `async` block can be used as `refresh_fn`.
+/// ```rust
+/// use std::future::Future;
+/// use generic_cache::Object;
+/// 
+/// fn load() -> impl Future<Output = Result<u16, ()>> {
+///     async { Ok(200) }
+/// }
+/// # tokio_test::block_on(async {
+/// let mut cached = Object::new(std::time::Duration::from_secs(1), 100, load);
+/// cached.refresh().await.unwrap();
+/// assert_eq!(*cached.get().unwrap(), 200);
+/// # })
+/// ```
+#[derive(Clone)]
+pub struct Object<T, F, E = ()> {
     ttl: Duration,
     last_update: SystemTime,
     obj: T,
-    refresh_fn: F
+    refresh_fn: F,
+    refresh_count: u64,
+    paused_at: Option<SystemTime>,
+    loaded: bool,
+    placeholder: bool,
+    max_stale: Option<Duration>,
+    invalidated: bool,
+    last_refresh_reason: Option<RefreshReason>,
+    error_ttl: Option<Duration>,
+    failure: Option<(E, SystemTime)>,
+    version: Option<u64>,
+    rate_limit: Option<(Duration, RateLimitAction)>,
+    last_call: Option<SystemTime>,
+    error_count: u64,
+    rejected_count: u64,
+    hit_count: u64,
+    miss_count: u64,
+    created: SystemTime,
+    max_lifetime: Option<Duration>,
+    hard_ttl: Option<Duration>,
+    ttl_bounds: Option<(Duration, Duration)>,
+    retry_at: Option<SystemTime>,
+    schedule: Option<(Schedule, SystemTime)>,
+    jitter: Option<(f64, f64)>,
+    last_success: Option<SystemTime>,
+    last_error: Option<SystemTime>,
+    forced_expiry: Option<SystemTime>,
+    last_refresh_duration: Option<Duration>,
+    post_transform: Option<PostTransform<T, E>>
 }
 impl<T, F, E> Debug for Object<T, F, E> where T: Debug, F: AsyncFnMut() -> Result<T, E> {
     fn fmt(&self, fmt: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
-        write!(fmt, "{{ttl: {} us, elapsed: {}, obj: {:#?}}}", self.ttl.as_micros(), self.last_update.elapsed().unwrap().as_millis(), self.obj)
+        write!(fmt, "{{ttl: {} us, elapsed: {}, obj: {:#?}}}", self.ttl.as_micros(), HumanDuration(clock::elapsed(self.last_update)), self.obj)
+    }
+}
+/// Format a duration with the largest fitting unit among ms, s and min so long-lived cache stay readable in log.
+struct HumanDuration(Duration);
+impl Display for HumanDuration {
+    fn fmt(&self, fmt: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        let secs = self.0.as_secs_f64();
+        if secs < 1.0 {
+            write!(fmt, "{} ms", self.0.as_millis())
+        } else if secs < 60.0 {
+            write!(fmt, "{:.1} s", secs)
+        } else {
+            write!(fmt, "{:.1} min", secs / 60.0)
+        }
+    }
+}
+/// Two cached objects are equal when their cached values are equal.
+/// `ttl`, last update time and `refresh_fn` are not compared.
+impl<T, F, E> PartialEq for Object<T, F, E> where T: PartialEq, F: AsyncFnMut() -> Result<T, E> {
+    fn eq(&self, other: &Self) -> bool {
+        self.obj == other.obj
+    }
+}
+impl<T, F, E> Eq for Object<T, F, E> where T: Eq, F: AsyncFnMut() -> Result<T, E> {}
+/// Hash only the cached value. Timing fields are excluded to stay consistent with [PartialEq].
+impl<T, F, E> Hash for Object<T, F, E> where T: Hash, F: AsyncFnMut() -> Result<T, E> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.obj.hash(state)
     }
 }
 /// A trait to provide a type that hides async refresh function.
@@ -151,7 +609,7 @@ impl<T, F, E> CachedObject<T, E> for Object<T, F, E> where F: AsyncFnMut() -> Re
     }
     #[inline(always)]
     fn get(&self) -> Result<&T, TimeoutError> {
-        Object::get(&self)
+        Object::get(self)
     }
     #[inline(always)]
     fn get_or_refresh<'a>(&'a mut self) -> Pin<Box<dyn Future<Output = Result<&'a T, E>> + 'a>> where T: 'a {
@@ -159,160 +617,2577 @@ impl<T, F, E> CachedObject<T, E> for Object<T, F, E> where F: AsyncFnMut() -> Re
     }
     #[inline(always)]
     fn time_remain(&self) -> Duration {
-        Object::time_remain(&self)
+        Object::time_remain(self)
     }
 }
-impl<T, F, E> Object<T, F, E> where F: AsyncFnMut() -> Result<T, E> { 
-    /// Create a new cached Object with default value specify in second argument. 
-    /// `ttl` is "time to live" which is duration that the cached value will be return.
-    /// `refresh_fn` is a function to refresh value and last update time.
-    pub fn new(ttl: Duration, obj: T, refresh_fn: F) -> Object<T, F, E> {
+/// Methods that don't call `refresh_fn` so they are available whatever signature `refresh_fn` has.
+impl<T, F, E> Object<T, F, E> {
+    /// Build the initial state of an [Object] with a value that was just updated.
+    fn init(ttl: Duration, obj: T, refresh_fn: F) -> Object<T, F, E> {
         Object {
             ttl,
-            last_update: SystemTime::now(),
+            last_update: clock::now(),
             obj,
-            refresh_fn
+            refresh_fn,
+            refresh_count: 0,
+            paused_at: None,
+            loaded: true,
+            placeholder: true,
+            max_stale: None,
+            invalidated: false,
+            last_refresh_reason: None,
+            error_ttl: None,
+            failure: None,
+            version: None,
+            rate_limit: None,
+            last_call: None,
+            error_count: 0,
+            rejected_count: 0,
+            hit_count: 0,
+            miss_count: 0,
+            created: clock::now(),
+            max_lifetime: None,
+            hard_ttl: None,
+            ttl_bounds: None,
+            retry_at: None,
+            schedule: None,
+            jitter: None,
+            last_success: None,
+            last_error: None,
+            forced_expiry: None,
+            last_refresh_duration: None,
+            post_transform: None
         }
     }
-    /// Create a new cached Object and immediately refresh the value instead of using default value.
-    /// `ttl` is "time to live" which is duration that the cached value will be return.
-    /// `refresh_fn` is a function to refresh value and last update time.
-    /// The different from `new` function is that it is async and it immediately call `refresh_fn`.
-    pub async fn new_and_refresh(ttl: Duration, mut refresh_fn: F) -> Result<Object<T, F, E>, E> {
-        let v = refresh_fn().await?;
-        let obj = Object {
+    /// Rebuild the cache with the value and `refresh_fn` returned by `f` while every other state is kept as is,
+    /// except the transform of [Object::set_post_transform()] which can't apply to a value of another type.
+    fn map_parts<U, G>(self, f: impl FnOnce(T, F) -> (U, G)) -> Object<U, G, E> {
+        let Object { ttl, last_update, obj, refresh_fn, refresh_count, paused_at, loaded, placeholder, max_stale, invalidated, last_refresh_reason, error_ttl, failure, version, rate_limit, last_call, error_count, rejected_count, hit_count, miss_count, created, max_lifetime, hard_ttl, ttl_bounds, retry_at, schedule, jitter, last_success, last_error, forced_expiry, last_refresh_duration, post_transform: _ } = self;
+        let (obj, refresh_fn) = f(obj, refresh_fn);
+        Object {
             ttl,
-            last_update: SystemTime::now(),
-            obj: v,
-            refresh_fn
-        };
-        Ok(obj)
+            last_update,
+            obj,
+            refresh_fn,
+            refresh_count,
+            paused_at,
+            loaded,
+            placeholder,
+            max_stale,
+            invalidated,
+            last_refresh_reason,
+            error_ttl,
+            failure,
+            version,
+            rate_limit,
+            last_call,
+            error_count,
+            rejected_count,
+            hit_count,
+            miss_count,
+            created,
+            max_lifetime,
+            hard_ttl,
+            ttl_bounds,
+            retry_at,
+            schedule,
+            jitter,
+            last_success,
+            last_error,
+            forced_expiry,
+            last_refresh_duration,
+            post_transform: None
+        }
     }
-    /// Refresh cache immediately and update last update time if refresh success.
-    pub async fn refresh(&mut self) -> Result<(), E> {
-        self.obj = (self.refresh_fn)().await?;
-        self.last_update = SystemTime::now();
-        Ok(())
+    /// Time elapsed since last update. It stop growing while the cache is paused.
+    fn age(&self) -> Duration {
+        self.age_at(clock::now())
+    }
+    /// Time elapsed between last update and `now`. It is zero if `now` is before last update.
+    fn age_at(&self, now: SystemTime) -> Duration {
+        self.paused_at.unwrap_or(now).duration_since(self.last_update).unwrap_or_default()
+    }
+    /// `ttl` of the current value, i.e. `ttl` reduced by the jitter of [Object::with_ttl_jitter()] if any.
+    fn effective_ttl(&self) -> Duration {
+        self.jitter.map_or(self.ttl, |(jitter, roll)| Duration::try_from_secs_f64(self.ttl.as_secs_f64() * (1.0 - jitter * roll)).unwrap_or(self.ttl).min(self.ttl))
+    }
+    /// Check whether the value is expired at `now`, either because `ttl` elapsed or it is invalidated.
+    #[inline]
+    fn is_expired_at(&self, now: SystemTime) -> bool {
+        self.invalidated || self.deadline_over_at(now) || (self.paused_at.is_none() && self.age_at(now) > self.hard_ttl.unwrap_or(self.effective_ttl()))
+    }
+    /// Time remain at `now` before the lifetime set by [Object::with_max_lifetime()] is over.
+    /// It is `None` if there's no max lifetime.
+    fn lifetime_remain_at(&self, now: SystemTime) -> Option<Duration> {
+        self.max_lifetime.map(|max_lifetime| max_lifetime.saturating_sub(now.duration_since(self.created).unwrap_or_default()))
+    }
+    /// Check whether the lifetime set by [Object::with_max_lifetime()] is over at `now`.
+    fn lifetime_over_at(&self, now: SystemTime) -> bool {
+        self.lifetime_remain_at(now) == Some(Duration::ZERO)
+    }
+    /// Time remain at `now` before the value expire regardless of `ttl`, i.e. before its lifetime is over,
+    /// its scheduled expiry set by [Object::with_schedule()] or the expiry forced by [Object::force_expire_at()].
+    /// It is `None` if there's none of them.
+    fn deadline_remain_at(&self, now: SystemTime) -> Option<Duration> {
+        let scheduled = self.schedule.map(|(_, expire_at)| expire_at);
+        let remain = |expire_at: SystemTime| expire_at.duration_since(now).unwrap_or_default();
+        [self.lifetime_remain_at(now), scheduled.map(remain), self.forced_expiry.map(remain)].into_iter().flatten().min()
+    }
+    /// Check whether the deadline of [Object::deadline_remain_at()] is over at `now`.
+    fn deadline_over_at(&self, now: SystemTime) -> bool {
+        self.deadline_remain_at(now) == Some(Duration::ZERO)
+    }
+    /// Return why the value must be refreshed before it is returned by `get_or_refresh` family
+    /// or `None` if the cached value can be returned as is, including a stale value before the hard `ttl`
+    /// of [Object::new_tiered()].
+    fn refresh_reason(&self) -> Option<RefreshReason> {
+        self.refresh_reason_at(clock::now())
+    }
+    /// Same as [Object::refresh_reason()] but evaluated as if current time is `now`.
+    fn refresh_reason_at(&self, now: SystemTime) -> Option<RefreshReason> {
+        if !self.loaded {
+            Some(RefreshReason::NotLoaded)
+        } else if self.invalidated {
+            Some(RefreshReason::Invalidated)
+        } else if self.deadline_over_at(now) || (self.paused_at.is_none() && self.age_at(now) >= self.hard_ttl.unwrap_or(self.effective_ttl())) {
+            Some(RefreshReason::Expired)
+        } else {
+            None
+        }
+    }
+    /// Same as [Object::refresh_reason()] but also count the lookup as a hit or a miss.
+    fn lookup(&mut self) -> Option<RefreshReason> {
+        let reason = self.refresh_reason();
+        self.record_lookup(reason.is_none());
+        reason
+    }
+    fn record_lookup(&mut self, hit: bool) {
+        if hit {
+            self.hit_count += 1;
+        } else {
+            self.miss_count += 1;
+        }
+    }
+    /// Record a successful call of `refresh_fn` that started at `started`, see [Object::since_last_success()].
+    fn record_success(&mut self, started: SystemTime) {
+        self.last_success = Some(clock::now());
+        self.last_refresh_duration = Some(clock::elapsed(started));
+    }
+    /// Count a failed call of `refresh_fn` that started at `started`, see [Object::since_last_error()].
+    fn record_error(&mut self, started: SystemTime) {
+        self.error_count += 1;
+        self.last_error = Some(clock::now());
+        self.last_refresh_duration = Some(clock::elapsed(started));
+    }
+    /// Check whether the cached value is allowed to be returned after it has expired.
+    fn can_serve_stale(&self) -> bool {
+        self.loaded && self.max_stale.is_none_or(|max_stale| self.age() <= self.effective_ttl().saturating_add(max_stale))
+    }
+    /// Set last update time to now. If the cache is paused, the pause is re-based to now as well
+    /// so the fresh value keep its full `ttl` when it is resumed.
+    fn mark_updated(&mut self) {
+        let now = clock::now();
+        if self.lifetime_over_at(now) {
+            self.created = now;
+        }
+        self.last_update = now;
+        self.reroll_jitter();
+        if let Some((schedule, _)) = self.schedule {
+            self.schedule = Some((schedule, schedule(now)));
+        }
+        self.loaded = true;
+        self.placeholder = false;
+        self.invalidated = false;
+        self.failure = None;
+        self.retry_at = None;
+        self.forced_expiry = None;
+        if self.paused_at.is_some() {
+            self.paused_at = Some(now);
+        }
     }
     /// Read current cached value or return Error if cache is already expired.
+    #[inline]
     pub fn get(&self) -> Result<&T, TimeoutError> {
-        if self.last_update.elapsed().unwrap() > self.ttl {
+        self.get_at(clock::now())
+    }
+    /// Same as [Object::get()] but the expiry is evaluated as if current time is `now`.
+    /// This is useful for replaying historical decision or testing expiry boundary deterministically.
+    #[inline]
+    pub fn get_at(&self, now: SystemTime) -> Result<&T, TimeoutError> {
+        if !self.loaded || self.is_expired_at(now) {
             return Err(TimeoutError {})
         }
         Ok(&self.obj)
     }
-    /// Read current cached value or refresh the value if it is already expired then
-    /// return the new value.
-    pub async fn get_or_refresh(&mut self) -> Result<&T, E> {
-        if self.last_update.elapsed().unwrap() >= self.ttl {
-            self.obj = (self.refresh_fn)().await?;
+    /// Same as [Object::get()] but tell why there's no value to return. It is [CacheError::NotLoaded] if there's no
+    /// value, e.g. before the first load of [Object::new_lazy()] or after the value is evicted by [ErrorAction::Evict],
+    /// or [CacheError::Expired] if the value is expired.
+    pub fn try_get(&self) -> Result<&T, CacheError<E>> {
+        if !self.loaded {
+            return Err(NotLoaded.into())
         }
-        Ok(&self.obj)
+        Ok(self.get()?)
+    }
+    /// Read current cached value regardless of its expiry. Return `None` only if there's no value, e.g.
+    /// before the first load of [Object::new_lazy()] or after the value is evicted by [ErrorAction::Evict].
+    pub fn peek(&self) -> Option<&T> {
+        self.loaded.then_some(&self.obj)
+    }
+    /// Return an owned clone of the current value without checking expiry and without refreshing,
+    /// e.g. to snapshot the latest known value. Unlike [Object::peek()], it can't tell that there's no value
+    /// so the placeholder of [Object::new_lazy()] is returned before the first load.
+    pub fn clone_value(&self) -> T where T: Clone {
+        self.obj.clone()
+    }
+    /// Synchronous fast path of [Object::get_or_refresh()]. Return the cached value if it can be returned
+    /// without refresh or `None` if a refresh is needed. Hot read path can call this first and only
+    /// await [Object::get_or_refresh()] on a miss so a cache hit never create or poll a future.
+    /// ```rust
+    /// use generic_cache::Object;
+    ///
+    /// # tokio_test::block_on(async {
+    /// let mut cached = Object::new(std::time::Duration::from_secs(1), 100, async || {Ok::<u16, ()>(200)});
+    /// let value = match cached.get_fast() {
+    ///     Some(value) => *value,
+    ///     None => *cached.get_or_refresh().await.unwrap()
+    /// };
+    /// assert_eq!(value, 100);
+    /// # })
+    /// ```
+    pub fn get_fast(&self) -> Option<&T> {
+        match self.refresh_reason() {
+            None => Some(&self.obj),
+            Some(_) => None
+        }
+    }
+    /// Same as [Object::get()] but apply a fallible projection `f`, e.g. parsing or downcasting, to the value.
+    /// The outer `Result` tell whether the cache is expired while the inner one is the result of `f`
+    /// so the two failures can be handled separately.
+    pub fn try_get_mapped<U, Err2>(&self, f: impl Fn(&T) -> Result<U, Err2>) -> Result<Result<U, Err2>, TimeoutError> {
+        self.get().map(f)
+    }
+    /// Same as [Object::get()] but return a copy of the value so the result doesn't borrow the cache.
+    pub fn get_copy(&self) -> Result<T, TimeoutError> where T: Copy {
+        self.get().copied()
+    }
+    /// Replace the cached value manually without calling `refresh_fn`. The new value is valid for full `ttl`.
+    pub fn set(&mut self, obj: T) {
+        self.obj = obj;
+        self.mark_updated();
+    }
+    /// Same as [Object::set()] but return the previous value, e.g. to clean it up after a config reload.
+    /// The previous value is returned even if it is expired or it is the placeholder of a lazy cache.
+    pub fn swap(&mut self, obj: T) -> T {
+        let previous = std::mem::replace(&mut self.obj, obj);
+        self.mark_updated();
+        previous
     }
     /// Get time remain that the cache still valid.
     /// In other word, time remain before it return [TimeoutError] on [Object::get] function.
     pub fn time_remain(&self) -> Duration {
-        let elapsed = self.last_update.elapsed().unwrap();
-        if elapsed > self.ttl {
-            Duration::from_micros(0u64)
-        } else {
-            self.ttl - elapsed
+        self.time_remain_at(clock::now())
+    }
+    /// Same as [Object::time_remain()] but evaluated as if current time is `now`.
+    fn time_remain_at(&self, now: SystemTime) -> Duration {
+        if self.invalidated {
+            return Duration::ZERO
         }
+        let remain = self.effective_ttl().saturating_sub(self.age_at(now));
+        self.deadline_remain_at(now).map_or(remain, |deadline| remain.min(deadline))
     }
-}
-#[cfg(test)]
-mod tests {
-    use core::time;
-    use std::{thread::sleep, time::Duration};
-
-    use super::*;
-
-    #[test]
-    fn simple_cache() {
-        let cached = Object::new(Duration::from_secs(1), 100, async || {Ok::<u16, ()>(200)});
-        let first = cached.get().unwrap();
-        let second = cached.get().unwrap();
-        assert_eq!(*first, 100, "Expect {} to equals {}", *first, 0);
-        assert_eq!(first, second, "Expect {} to equals {}", first, second);
+    /// Time remain before the cached value expire or [Duration::ZERO] if it is expired or there's no value.
+    /// Unlike [Object::time_remain()], it is also zero when the value was evicted so it can be used directly
+    /// as `max-age` of a HTTP response.
+    pub fn ttl_remaining_or_zero(&self) -> Duration {
+        if !self.loaded {
+            return Duration::ZERO
+        }
+        self.time_remain()
     }
-    #[tokio::test]
-    async fn simple_refresh() {
-        let mut cached = Object::new(Duration::from_secs(1), 100, async || {Ok::<u16, ()>(200)});
-        let first = *cached.get().unwrap();
-        cached.refresh().await.unwrap();
-        let second = *cached.get().unwrap();
-        assert_eq!(first, 100, "Expect {} to equals {}", first, 100);
-        assert_eq!(second, 200, "Expect {} to equals {}", first, 200);
+    /// Fraction of the value lifetime that remain, from 1.0 when the value was just refreshed down to 0.0 once
+    /// it expired. The lifetime end at the same deadline as [Object::time_remain()], i.e. the earliest of `ttl`,
+    /// [Object::with_max_lifetime()], [Object::with_schedule()] and [Object::force_expire_at()].
+    /// It is also 0.0 if there's no value, `ttl` is zero or last update time is in the future due to clock change.
+    pub fn freshness_ratio(&self) -> f64 {
+        let now = clock::now();
+        if !self.loaded || self.is_expired_at(now) || self.effective_ttl().is_zero() || self.last_update > self.paused_at.unwrap_or(now) {
+            return 0.0
+        }
+        let remain = self.time_remain_at(now);
+        let lifetime = self.age_at(now).saturating_add(remain);
+        if lifetime.is_zero() {
+            return 0.0
+        }
+        (remain.as_secs_f64() / lifetime.as_secs_f64()).clamp(0.0, 1.0)
     }
-    #[tokio::test]
-    async fn simple_no_cache() {
-        let mut cached = Object::new(Duration::ZERO, 100, async || {Ok::<u16, ()>(200)});
-        let first = *cached.get_or_refresh().await.unwrap();
-        sleep(time::Duration::from_millis(1));
-        let second = *cached.get_or_refresh().await.unwrap();
-        assert_eq!(first, 200, "Expect {} to equals {}", first, 200);
-        assert_eq!(first, second, "Expect {} to equals {}", first, second);
+    /// Check whether the cached value was last updated more than `threshold` ago, regardless of `ttl`.
+    /// This let a health check use its own staleness limit, e.g. alert when the value is older than 5 minutes.
+    pub fn age_exceeds(&self, threshold: Duration) -> bool {
+        self.age() > threshold
     }
-    #[tokio::test]
-    async fn simple_expire_check() {
-        let mut cached = Object::new(Duration::from_millis(1), 100, async || {Ok::<u16, ()>(200)});
-        let first = *cached.get().unwrap();
-        sleep(time::Duration::from_millis(1));
-        if let Ok(_) = cached.get() {
-            panic!("Cache should be expired but it is not.")
+    /// Return a future that resolve once the cached value expire. This allow a proactive refresh loop
+    /// that refresh right when the value expire.
+    /// The expiry time is computed when this function is called so later refresh, pause or
+    /// invalidation doesn't change when the future resolve. It doesn't borrow the cache.
+    /// 
+    /// Require `tokio` feature and it must be awaited within a tokio runtime with time enabled.
+    /// ```rust
+    /// use std::time::Duration;
+    /// use generic_cache::Object;
+    /// 
+    /// # tokio_test::block_on(async {
+    /// let mut cached = Object::new(Duration::from_millis(10), 100, async || {Ok::<u16, ()>(200)});
+    /// for _ in 0..2 {
+    ///     cached.expired().await;
+    ///     cached.refresh().await.unwrap();
+    /// }
+    /// assert_eq!(cached.refresh_count(), 2);
+    /// # })
+    /// ```
+    #[cfg(feature = "tokio")]
+    pub fn expired(&self) -> impl Future<Output = ()> + 'static {
+        tokio::time::sleep(self.time_remain())
+    }
+    /// Check whether the cache hold a real value, whether it is expired or not. It is `false` after
+    /// [Object::new_lazy()] until the first successful refresh or [Object::set()] and after the value is
+    /// evicted by [ErrorAction::Evict].
+    pub fn is_loaded(&self) -> bool {
+        self.loaded
+    }
+    /// Summarize the current status of the cache. This never panic even if the system clock went backward.
+    pub fn state(&self) -> CacheState {
+        if !self.loaded {
+            return CacheState::NeverLoaded
+        }
+        let now = clock::now();
+        let age = self.age_at(now);
+        if self.is_expired_at(now) {
+            CacheState::Expired { age }
+        } else if let Some(hard_ttl) = self.hard_ttl.filter(|_| self.paused_at.is_none() && age >= self.effective_ttl()) {
+            let remaining = hard_ttl.saturating_sub(age);
+            CacheState::Stale { age, remaining: self.deadline_remain_at(now).map_or(remaining, |deadline| remaining.min(deadline)) }
         } else {
-            cached.refresh().await.unwrap();
+            let remaining = self.effective_ttl().saturating_sub(age);
+            CacheState::Valid { remaining: self.deadline_remain_at(now).map_or(remaining, |deadline| remaining.min(deadline)) }
         }
-        let second = *cached.get().unwrap();
-        assert_ne!(first, second, "Expect {} to equals {}", first, second);
     }
-    #[tokio::test]
-    async fn immediate_refresh() {
-        let mut cached = Object::new_and_refresh(Duration::from_secs(1), async || {Ok::<u16, ()>(200)}).await.unwrap();
-        let first = *cached.get_or_refresh().await.unwrap();
-        let second = *cached.get_or_refresh().await.unwrap();
-        assert_eq!(first, second, "Expect {} to equals {}", first, second);
+    /// Take a consistent snapshot of age, remaining `ttl`, expiry, load status and counters, all evaluated
+    /// at the same instant. When the cache is behind a lock, e.g. `RwLock<Object>`, the lock only need to be
+    /// held for this call.
+    pub fn observe(&self) -> Observation {
+        let now = clock::now();
+        let age = self.age_at(now);
+        let is_expired = self.refresh_reason_at(now).is_some();
+        let remaining_ttl = if is_expired {
+            Duration::ZERO
+        } else {
+            let remaining = self.effective_ttl().saturating_sub(age);
+            self.deadline_remain_at(now).map_or(remaining, |deadline| remaining.min(deadline))
+        };
+        Observation {
+            age,
+            remaining_ttl,
+            is_expired: is_expired || remaining_ttl.is_zero(),
+            is_loaded: self.loaded,
+            stats: self.stats()
+        }
     }
-    #[tokio::test]
-    async fn time_remain_validate() {
-        let mut cached = Object::new(Duration::from_secs(5), 100, async || {Ok::<u16, ()>(200)});
-        sleep(Duration::from_secs(1));
-        let original_remain = cached.time_remain();
-        println!("Original time remain is {:?}", original_remain);
-        cached.refresh().await.unwrap();
-        let new_remain = cached.time_remain();
-        println!("New time remain is {:?}", new_remain);
-        assert!(new_remain > original_remain, "Original time remain should be less than a fresh new value time remain.");
+    /// Freeze the cache so the value neither expire nor get automatically refreshed.
+    /// [Object::get()] and [Object::get_or_refresh()] treat the value as valid until [Object::resume()] is called.
+    /// Explicit [Object::refresh()] still work while paused.
+    pub fn pause(&mut self) {
+        if self.paused_at.is_none() {
+            self.paused_at = Some(clock::now());
+        }
     }
-    #[test]
-    fn simple_object() {
-        struct Dummy {
-            v: u8
+    /// Resume normal expiry after [Object::pause()]. The time remain before the pause is preserved.
+    pub fn resume(&mut self) {
+        if let Some(paused_at) = self.paused_at.take() {
+            self.last_update += clock::elapsed(paused_at);
         }
-        let cached = Object::new(Duration::from_secs(1), Dummy {v: 1}, async || {Ok::<Dummy, ()>(Dummy {v: 2})});
-        let Dummy { v: v1} = cached.get().unwrap();
-        let Dummy { v: v2} = cached.get().unwrap();
-        assert_eq!(*v1, 1, "Expect {} to equals {}", v1, 1);
-        assert_eq!(v1, v2, "Expect {} to equals {}", v1, v2);
     }
-    #[tokio::test]
-    async fn simple_object_mut() {
-        let mut count = 0u8;
-        struct Dummy {
-            v: u8
+    /// Check whether the cache is paused by [Object::pause()].
+    pub fn is_paused(&self) -> bool {
+        self.paused_at.is_some()
+    }
+    /// Mark the cached value as expired regardless of `ttl`, even when the cache is paused.
+    /// The next `get_or_refresh` call will refresh it.
+    pub fn invalidate(&mut self) {
+        self.invalidated = true;
+    }
+    /// Expire the cached value at `when` regardless of `ttl`, e.g. to invalidate every instance at the same
+    /// wall clock time. The value expire immediately if `when` is in the past. Like [Object::invalidate()],
+    /// it only apply to the current value so the next refreshed value follow `ttl` again.
+    /// `when` is compared against [clock::now()], which differ from [SystemTime::now()] under `tokio-clock`
+    /// feature when tokio time is paused or advanced.
+    pub fn force_expire_at(&mut self, when: SystemTime) {
+        self.forced_expiry = Some(when);
+    }
+    /// Why `refresh_fn` was called the last time, whether it success or not.
+    /// Return `None` if it was never called.
+    pub fn last_refresh_reason(&self) -> Option<RefreshReason> {
+        self.last_refresh_reason
+    }
+    /// Number of time `refresh_fn` was called, including the failed one.
+    /// It is useful to diagnose over refreshing.
+    pub fn refresh_count(&self) -> u64 {
+        self.refresh_count
+    }
+    /// Time since `refresh_fn` last succeeded or `None` if it never did. Unlike the age of the value, it isn't
+    /// reset by a value set manually, e.g. by [Object::set()]. Together with [Object::since_last_error()], it
+    /// tell a healthy cache apart from one that keep serving a stale value because every refresh fail.
+    pub fn since_last_success(&self) -> Option<Duration> {
+        self.last_success.map(clock::elapsed)
+    }
+    /// Time since `refresh_fn` last failed or `None` if it never did.
+    pub fn since_last_error(&self) -> Option<Duration> {
+        self.last_error.map(clock::elapsed)
+    }
+    /// How long the last refresh took, whether it success or not, or `None` if `refresh_fn` was never called.
+    /// A refresh hedged by [Object::refresh_hedged()] is measured as a whole.
+    pub fn last_refresh_duration(&self) -> Option<Duration> {
+        self.last_refresh_duration
+    }
+    /// Snapshot of the counters of this cache.
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            hits: self.hit_count,
+            misses: self.miss_count,
+            refreshes: self.refresh_count,
+            errors: self.error_count,
+            rejected_refreshes: self.rejected_count
         }
-        let mut cached = Object::new(Duration::from_secs(1), Dummy {v: 1}, async || {
-            count += 1;
-            Ok::<Dummy, ()>(Dummy {v: 2})
-        });
-        let Dummy { v: v1} = cached.get().unwrap();
-        let Dummy { v: v2} = cached.get().unwrap();
-        let original = *v1;
-        assert_eq!(*v1, 1, "Expect {} to equals {}", v1, 1);
-        assert_eq!(*v1, *v2, "Expect {} to equals {}", v1, v2);
-        cached.refresh().await.expect("to get a new value");
-        let Dummy { v: new_v} = cached.get().unwrap();
-        assert_ne!(original, *new_v, "Expect to get a new value");
+    }
+    /// Get the configured `ttl`.
+    pub fn ttl(&self) -> Duration {
+        self.ttl
+    }
+    /// Change the `ttl`. The new `ttl` also apply to the currently cached value.
+    pub fn set_ttl(&mut self, ttl: Duration) {
+        self.ttl = ttl;
+    }
+    /// Change the `ttl` and treat the cached value as freshly loaded in one call so the previous age never
+    /// apply to the new `ttl`. The value is then valid for the full new `ttl`.
+    /// If there's no value, e.g. before the first load of [Object::new_lazy()], only `ttl` is changed.
+    pub fn set_ttl_and_touch(&mut self, ttl: Duration) {
+        self.ttl = ttl;
+        self.touch();
+    }
+    /// Treat the cached value as freshly loaded so it is valid for full `ttl` from now, without calling `refresh_fn`.
+    /// If there's no value, e.g. before the first load of [Object::new_lazy()], nothing is changed.
+    pub fn touch(&mut self) {
+        if self.loaded {
+            self.mark_updated();
+        }
+    }
+    /// Shorten the `ttl` of every value by a random fraction of up to `jitter`, e.g. 0.1 for up to 10%, so
+    /// caches created at the same time don't all expire together. A new random reduction is drawn whenever
+    /// the value is updated. `jitter` is clamped between 0.0 and 1.0 and NaN `jitter` is treated as zero.
+    /// [Object::ttl()] still return the configured `ttl`.
+    pub fn with_ttl_jitter(mut self, jitter: f64) -> Self {
+        let jitter = if jitter.is_nan() { 0.0 } else { jitter.clamp(0.0, 1.0) };
+        self.jitter = Some((jitter, 0.0));
+        self.reroll_jitter();
+        self
+    }
+    /// Draw a new random reduction for the jitter of [Object::with_ttl_jitter()] without refreshing the value,
+    /// e.g. to further spread expiry after a configuration change. The expiry is recomputed from the existing
+    /// last update time. It does nothing without jitter.
+    pub fn reroll_jitter(&mut self) {
+        if let Some((jitter, _)) = self.jitter {
+            self.jitter = Some((jitter, random_fraction()));
+        }
+    }
+    /// Limit the `ttl` computed by [Object::scale_ttl()] to be between `min` and `max`.
+    /// `max` lower than `min` is raised to `min`.
+    pub fn with_ttl_bounds(mut self, min: Duration, max: Duration) -> Self {
+        self.ttl_bounds = Some((min, max.max(min)));
+        self
+    }
+    /// Multiply the current `ttl` by `factor`, e.g. lengthen it while the backend is stable and shorten it
+    /// during churn. The result is clamped to the bounds set by [Object::with_ttl_bounds()] if any.
+    /// Negative or NaN `factor` is treated as zero. Like [Object::set_ttl()], it also apply to the currently cached value.
+    pub fn scale_ttl(&mut self, factor: f64) {
+        let scaled = if factor > 0.0 {
+            Duration::try_from_secs_f64(self.ttl.as_secs_f64() * factor).unwrap_or(Duration::MAX)
+        } else {
+            Duration::ZERO
+        };
+        let (min, max) = self.ttl_bounds.unwrap_or((Duration::ZERO, Duration::MAX));
+        self.ttl = scaled.clamp(min, max);
+    }
+    /// Get the configured `ttl` in milliseconds. This is the unit used by `ttl` before version 0.3.0.
+    pub fn ttl_millis(&self) -> u128 {
+        self.ttl.as_millis()
+    }
+    /// Change the `ttl` using milliseconds. This is the unit used by `ttl` before version 0.3.0.
+    /// Value larger than `u64::MAX` milliseconds is saturated.
+    pub fn set_ttl_millis(&mut self, ms: u128) {
+        self.ttl = Duration::from_millis(u64::try_from(ms).unwrap_or(u64::MAX));
+    }
+    /// Consume the cache and return the last cached value regardless of its expiry.
+    /// 
+    /// [Object] doesn't implement [Drop] because it would prevent moving the value out. For resource backed value, e.g. file handle or connection,
+    /// this is the supported way to run a cleanup with the final value.
+    /// ```rust
+    /// use generic_cache::Object;
+    /// 
+    /// let cached = Object::new(std::time::Duration::from_secs(1), vec![1u8], async || {Ok::<Vec<u8>, ()>(vec![2u8])});
+    /// let last = cached.into_inner();
+    /// drop(last); // cleanup the final value here
+    /// ```
+    pub fn into_inner(self) -> T {
+        self.obj
+    }
+    /// Consume the cache and return its `ttl`, last update time, last cached value and `refresh_fn`,
+    /// e.g. to persist the value and rebuild the cache later. The value is returned regardless of its expiry.
+    /// If there's no value, the placeholder is returned. Use [Object::try_into_parts()] to avoid that.
+    pub fn into_parts(self) -> (Duration, SystemTime, T, F) {
+        (self.ttl, self.last_update, self.obj, self.refresh_fn)
+    }
+    /// Same as [Object::into_parts()] but return [NotLoaded] instead of the placeholder if there's no value,
+    /// e.g. before the first load of [Object::new_lazy()], so a dummy value is never persisted by accident.
+    pub fn try_into_parts(self) -> Result<(Duration, SystemTime, T, F), NotLoaded> {
+        if !self.loaded {
+            return Err(NotLoaded)
+        }
+        Ok(self.into_parts())
+    }
+    /// Create a new cached Object whose `refresh_fn` receive a context from each caller of
+    /// [Object::get_or_refresh_with()], e.g. a trace id or an auth token of the current request.
+    /// The cached value is shared by every caller regardless of the context it was refreshed with.
+    /// ```rust
+    /// use generic_cache::Object;
+    ///
+    /// # tokio_test::block_on(async {
+    /// let mut cached = Object::new_with_context(std::time::Duration::ZERO, 0, async |token: &String| {Ok::<usize, ()>(token.len())});
+    /// assert_eq!(*cached.get_or_refresh_with(&"abc".to_string()).await.unwrap(), 3);
+    /// # })
+    /// ```
+    pub fn new_with_context<Ctx: ?Sized>(ttl: Duration, obj: T, refresh_fn: F) -> Object<T, F, E> where F: AsyncFnMut(&Ctx) -> Result<T, E> {
+        Object::init(ttl, obj, refresh_fn)
+    }
+    /// Guarantee that `refresh_fn` is never called more than once per `min_interval`, whatever trigger
+    /// the refresh, e.g. manual refresh, expiry or invalidation. `action` decide whether a refresh
+    /// requested too soon wait or is skipped so the stale value is kept.
+    pub fn with_min_refresh_interval(mut self, min_interval: Duration, action: RateLimitAction) -> Self {
+        self.rate_limit = Some((min_interval, action));
+        self
+    }
+    /// Never call `refresh_fn` more than once per `interval`, even if `ttl` is shorter. In between, the
+    /// existing value is served as is. It is a shorthand of [Object::with_min_refresh_interval()] with
+    /// [RateLimitAction::ServeStale].
+    /// ```rust
+    /// use std::time::Duration;
+    /// use generic_cache::Object;
+    ///
+    /// # tokio_test::block_on(async {
+    /// let mut cached = Object::new(Duration::ZERO, 100, async || {Ok::<u16, ()>(200)}).with_refresh_at_most_every(Duration::from_secs(60));
+    /// cached.refresh().await.unwrap();
+    /// assert_eq!(cached.get_or_refresh().await, Ok(&200));
+    /// assert_eq!(cached.refresh_count(), 1);
+    /// # })
+    /// ```
+    pub fn with_refresh_at_most_every(self, interval: Duration) -> Self {
+        self.with_min_refresh_interval(interval, RateLimitAction::ServeStale)
+    }
+    /// Start the cache part-way through its `ttl` as if the value was updated `initial_age` ago.
+    /// Giving different offset, e.g. random one, to many caches created at once spread their expiry so
+    /// they don't all refresh at the same time. `initial_age` longer than `ttl` make the value expired right away.
+    pub fn with_initial_age(mut self, initial_age: Duration) -> Self {
+        let now = clock::now();
+        self.last_update = now.checked_sub(initial_age).unwrap_or(now);
+        self
+    }
+    /// Give the value a hard lifetime measured from creation instead of from last refresh.
+    /// Once `max_lifetime` elapsed, the value is expired even if it was recently refreshed and even while paused.
+    /// The next successful refresh after that start a new lifetime.
+    pub fn with_max_lifetime(mut self, max_lifetime: Duration) -> Self {
+        self.max_lifetime = Some(max_lifetime);
+        self
+    }
+    /// Expire the value at the instant computed by `schedule` instead of after a duration, e.g. every day at
+    /// midnight. `schedule` receive the time the value was updated and return when it expire. It is called
+    /// right away for the current value and again on every update. Time zone or cron expression handling is
+    /// up to `schedule`, e.g. with a time zone aware date library.
+    /// `ttl` is set to [Duration::MAX] so only the schedule expire the value. Like the lifetime of
+    /// [Object::with_max_lifetime()], the scheduled expiry also apply while paused.
+    /// ```rust
+    /// use std::time::{Duration, SystemTime, UNIX_EPOCH};
+    /// use generic_cache::Object;
+    ///
+    /// // Expire at the start of the next UTC hour.
+    /// fn next_hour(updated: SystemTime) -> SystemTime {
+    ///     let secs = updated.duration_since(UNIX_EPOCH).unwrap().as_secs();
+    ///     UNIX_EPOCH + Duration::from_secs((secs / 3600 + 1) * 3600)
+    /// }
+    /// let cached = Object::new(Duration::ZERO, 100, async || {Ok::<u16, ()>(200)}).with_schedule(next_hour);
+    /// assert!(cached.time_remain() <= Duration::from_secs(3600));
+    /// ```
+    pub fn with_schedule(mut self, schedule: fn(SystemTime) -> SystemTime) -> Self {
+        self.ttl = Duration::MAX;
+        self.schedule = Some((schedule, schedule(self.last_update)));
+        self
+    }
+    /// Apply the limit of [Object::with_min_refresh_interval()] right before `refresh_fn` is called.
+    /// Return `false` if the call must be skipped.
+    async fn throttle(&mut self) -> bool {
+        if let Some((min_interval, action)) = self.rate_limit {
+            let wait = self.last_call.map_or(Duration::ZERO, |last_call| min_interval.saturating_sub(clock::elapsed(last_call)));
+            if !wait.is_zero() {
+                match action {
+                    RateLimitAction::ServeStale if self.loaded => return false,
+                    RateLimitAction::ServeStale => (),
+                    #[cfg(feature = "tokio")]
+                    RateLimitAction::Wait => tokio::time::sleep(wait).await
+                }
+            }
+        }
+        self.last_call = Some(clock::now());
+        true
+    }
+    /// Same as [Object::get_or_refresh()] but `ctx` is passed to `refresh_fn` if a refresh is needed.
+    pub async fn get_or_refresh_with<Ctx: ?Sized>(&mut self, ctx: &Ctx) -> Result<&T, E> where F: AsyncFnMut(&Ctx) -> Result<T, E> {
+        if let Some(reason) = self.lookup() {
+            if !self.throttle().await {
+                return Ok(&self.obj)
+            }
+            self.refresh_count += 1;
+            self.last_refresh_reason = Some(reason);
+            let started = clock::now();
+            let result = match (self.refresh_fn)(ctx).await {
+                Ok(obj) => apply_post_transform(self.post_transform.as_ref(), obj).await,
+                Err(e) => Err(e)
+            };
+            self.obj = result.inspect_err(|_| self.record_error(started))?;
+            self.record_success(started);
+            self.mark_updated();
+        }
+        Ok(&self.obj)
+    }
+}
+impl<T, F, E> Object<T, F, E> where F: AsyncFnMut() -> Result<T, E> { 
+    /// Create a new cached Object with default value specify in second argument. 
+    /// `ttl` is "time to live" which is duration that the cached value will be return.
+    /// `refresh_fn` is a function to refresh value and last update time.
+    pub fn new(ttl: Duration, obj: T, refresh_fn: F) -> Object<T, F, E> {
+        Object::init(ttl, obj, refresh_fn)
+    }
+    /// Create a new cached Object with two expiry tiers.
+    /// Between `soft` and `hard`, [Object::get()], [Object::get_fast()] and every `get_or_refresh` method keep
+    /// returning the current value without calling `refresh_fn` and [Object::state()] return [CacheState::Stale]
+    /// so the caller can refresh it in the background, e.g. with [Object::refresh()] from another task. After `hard`,
+    /// the value is expired and [Object::get_or_refresh()] block on the refresh as usual.
+    /// [Object::ttl()] and [Object::time_remain()] refer to `soft`. `hard` shorter than `soft` is raised to `soft`.
+    pub fn new_tiered(soft: Duration, hard: Duration, obj: T, refresh_fn: F) -> Object<T, F, E> {
+        let mut obj = Object::init(soft, obj, refresh_fn);
+        obj.hard_ttl = Some(hard.max(soft));
+        obj
+    }
+    /// Create a new cached Object without any value. The first [Object::get_or_refresh()] load the value.
+    /// Until then, [Object::try_get()] return [CacheError::NotLoaded], [Object::get()] return [TimeoutError]
+    /// and [Object::state()] return [CacheState::NeverLoaded].
+    /// `T::default()` is only kept as placeholder and it is never returned.
+    pub fn new_lazy(ttl: Duration, refresh_fn: F) -> Object<T, F, E> where T: Default {
+        let mut obj = Object::init(ttl, T::default(), refresh_fn);
+        obj.loaded = false;
+        obj
+    }
+    /// Create a new cached Object whose initial value is a placeholder that is expired right away.
+    /// [Object::get()] return [TimeoutError] so the first [Object::get_or_refresh()] load the real value
+    /// while the placeholder stay readable by [Object::peek()]. Refreshed value use full `ttl` as usual.
+    /// Unlike [Object::new_lazy()], the placeholder is a usable value, e.g. it can be served as stale.
+    pub fn with_initial_and_lazy_ttl(ttl: Duration, placeholder: T, refresh_fn: F) -> Object<T, F, E> {
+        let mut obj = Object::init(ttl, placeholder, refresh_fn);
+        obj.last_update = SystemTime::UNIX_EPOCH;
+        obj
+    }
+    /// Create a new cached Object and immediately refresh the value instead of using default value.
+    /// `ttl` is "time to live" which is duration that the cached value will be return.
+    /// `refresh_fn` is a function to refresh value and last update time.
+    /// The different from `new` function is that it is async and it immediately call `refresh_fn`.
+    pub async fn new_and_refresh(ttl: Duration, mut refresh_fn: F) -> Result<Object<T, F, E>, E> {
+        let started = clock::now();
+        let v = refresh_fn().await?;
+        let mut obj = Object::init(ttl, v, refresh_fn);
+        obj.refresh_count = 1;
+        obj.placeholder = false;
+        obj.record_success(started);
+        Ok(obj)
+    }
+    /// Same as [Object::new_and_refresh()] but the initial refresh must complete within `timeout`.
+    /// Otherwise, [RefreshError::Timeout] is returned and no [Object] is created.
+    /// This prevent a service startup from blocking forever when the backend is slow or down.
+    /// 
+    /// Require `tokio` feature and it must be called within a tokio runtime with time enabled.
+    #[cfg(feature = "tokio")]
+    pub async fn try_new_and_refresh_with_timeout(ttl: Duration, refresh_fn: F, timeout: Duration) -> Result<Object<T, F, E>, RefreshError<E>> {
+        match tokio::time::timeout(timeout, Object::new_and_refresh(ttl, refresh_fn)).await {
+            Ok(result) => result.map_err(RefreshError::Refresh),
+            Err(_) => Err(RefreshError::Timeout(timeout))
+        }
+    }
+    /// Call `refresh_fn` then count the call and record its reason whether it success or not.
+    /// Return `None` without calling `refresh_fn` if the call is skipped by [Object::with_min_refresh_interval()].
+    async fn call_refresh_fn(&mut self, reason: RefreshReason) -> Result<Option<T>, E> {
+        if !self.throttle().await {
+            return Ok(None)
+        }
+        self.refresh_count += 1;
+        self.last_refresh_reason = Some(reason);
+        let started = clock::now();
+        let result = match (self.refresh_fn)().await {
+            Ok(obj) => apply_post_transform(self.post_transform.as_ref(), obj).await,
+            Err(e) => Err(e)
+        };
+        match result {
+            Ok(obj) => {
+                self.record_success(started);
+                Ok(Some(obj))
+            },
+            Err(e) => {
+                self.record_error(started);
+                Err(e)
+            }
+        }
+    }
+    /// Replace the cached value with a new one from `refresh_fn`.
+    async fn refresh_for(&mut self, reason: RefreshReason) -> Result<(), E> {
+        if let Some(obj) = self.call_refresh_fn(reason).await? {
+            self.obj = obj;
+            self.mark_updated();
+        }
+        Ok(())
+    }
+    /// Refresh cache immediately and update last update time if refresh success.
+    pub async fn refresh(&mut self) -> Result<(), E> {
+        self.refresh_for(RefreshReason::Manual).await
+    }
+    /// Refresh only when the cached value is older than `threshold`, regardless of `ttl`. It allow a
+    /// shorter "refresh" age than the "serve" age used by [Object::get()], e.g. refresh after 1 minute
+    /// while a value up to 5 minutes old is still served. Return whether the cached value was replaced.
+    /// It return `false` if the refresh was skipped by [Object::with_min_refresh_interval()].
+    pub async fn refresh_if_older_than(&mut self, threshold: Duration) -> Result<bool, E> {
+        if !self.age_exceeds(threshold) {
+            return Ok(false)
+        }
+        let Some(obj) = self.call_refresh_fn(RefreshReason::Manual).await? else {
+            return Ok(false)
+        };
+        self.obj = obj;
+        self.mark_updated();
+        Ok(true)
+    }
+    /// Same as [Object::refresh()] but the value is loaded by `f` instead of the stored `refresh_fn`,
+    /// e.g. a forced full reload instead of the usual delta. The stored `refresh_fn` is left unchanged.
+    /// It count as a refresh in [Object::stats()] and it is limited by [Object::with_min_refresh_interval()]
+    /// like the stored `refresh_fn`.
+    pub async fn refresh_with(&mut self, f: impl AsyncFnOnce() -> Result<T, E>) -> Result<(), E> {
+        if !self.throttle().await {
+            return Ok(())
+        }
+        self.refresh_count += 1;
+        self.last_refresh_reason = Some(RefreshReason::Manual);
+        let started = clock::now();
+        let result = match f().await {
+            Ok(obj) => apply_post_transform(self.post_transform.as_ref(), obj).await,
+            Err(e) => Err(e)
+        };
+        self.obj = result.inspect_err(|_| self.record_error(started))?;
+        self.record_success(started);
+        self.mark_updated();
+        Ok(())
+    }
+    /// Same as [Object::refresh()] but a failed refresh is retried according to `policy`.
+    /// `sleep` is called with the delay between attempts so any async runtime timer can be used,
+    /// e.g. `tokio::time::sleep`. The cached value is only updated when an attempt success.
+    /// If every attempt fail, the error of the last attempt is returned.
+    pub async fn refresh_with_policy(&mut self, policy: RetryPolicy, mut sleep: impl AsyncFnMut(Duration)) -> Result<(), E> {
+        let mut retry = 0;
+        loop {
+            match self.refresh().await {
+                Err(_) if retry + 1 < policy.max_attempts => {
+                    sleep(policy.delay(retry)).await;
+                    retry += 1;
+                },
+                result => return result
+            }
+        }
+    }
+    /// Refresh cache immediately but instead of replacing the cached value, `merge` is called with the
+    /// cached value and the new value to combine them. Last update time is updated if refresh success.
+    pub async fn refresh_merging(&mut self, merge: impl FnOnce(&mut T, T)) -> Result<(), E> {
+        if let Some(obj) = self.call_refresh_fn(RefreshReason::Manual).await? {
+            merge(&mut self.obj, obj);
+            self.mark_updated();
+        }
+        Ok(())
+    }
+    /// Same as [Object::refresh()] but return the previous value along with the new one, e.g. for change data capture.
+    /// The previous value is `None` if there was no usable value before, e.g. it was evicted, or if the
+    /// refresh was skipped by [Object::with_min_refresh_interval()].
+    /// The previous value is moved out of the cache so it isn't cloned.
+    pub async fn refresh_diff(&mut self) -> Result<(Option<T>, &T), E> {
+        let Some(obj) = self.call_refresh_fn(RefreshReason::Manual).await? else {
+            return Ok((None, &self.obj))
+        };
+        let previous = std::mem::replace(&mut self.obj, obj);
+        let previous = self.loaded.then_some(previous);
+        self.mark_updated();
+        Ok((previous, &self.obj))
+    }
+    /// Same as [Object::refresh()] but when the new value equals the cached one, only last update time is updated.
+    /// The cached value is not replaced so downstream that hold the value, e.g. an [Arc] clone, is not invalidated.
+    /// Return whether the cached value was replaced. `WatchedCache` refresh this way so its subscribers
+    /// aren't notified of an equal value.
+    pub async fn refresh_if_changed(&mut self) -> Result<bool, E> where T: PartialEq {
+        let Some(obj) = self.call_refresh_fn(RefreshReason::Manual).await? else {
+            return Ok(false)
+        };
+        let changed = !self.loaded || obj != self.obj;
+        if changed {
+            self.obj = obj;
+        }
+        self.mark_updated();
+        Ok(changed)
+    }
+    /// Store a value pushed from outside, e.g. from an eventually consistent source, only if `version`
+    /// is greater than the version of the last replaced value. Older or same version is ignored so
+    /// out of order update cannot overwrite a newer value. The first pushed value is always stored.
+    /// Value from `refresh_fn` doesn't carry a version so it doesn't change the recorded version.
+    /// Return whether the cached value was replaced.
+    pub fn replace_if_newer(&mut self, value: T, version: u64) -> bool {
+        if self.version.is_some_and(|current| version <= current) {
+            return false
+        }
+        self.obj = value;
+        self.version = Some(version);
+        self.mark_updated();
+        true
+    }
+    /// Version recorded by the last successful [Object::replace_if_newer()].
+    pub fn version(&self) -> Option<u64> {
+        self.version
+    }
+    /// Call `refresh_fn` and return its result without storing it. The cached value, last update time
+    /// and statistic are left untouched. This is useful to pre-fetch a value for somewhere else.
+    /// It need `refresh_fn` to be [AsyncFn], e.g. a plain `async fn`, so it can be called through `&self`.
+    pub async fn run_refresh_fn(&self) -> Result<T, E> where F: AsyncFn() -> Result<T, E> {
+        (self.refresh_fn)().await
+    }
+    /// Same as [Object::refresh()] but if `refresh_fn` hasn't completed within `hedge_after`, a second call is
+    /// started concurrently and the first successful one is used. The other call is cancelled by dropping it.
+    /// If one of the calls fail, the other one is still awaited. If both fail, the error of the last one is returned.
+    /// It need `refresh_fn` to be [AsyncFn], e.g. a plain `async fn`, so it can be called twice at the same time.
+    /// Every call count as a refresh in [Object::stats()]. [Object::with_min_refresh_interval()] limit the
+    /// hedged refresh as a whole so the second call doesn't wait for the interval.
+    ///
+    /// Require `tokio` feature and it must be called within a tokio runtime with time enabled.
+    #[cfg(feature = "tokio")]
+    pub async fn refresh_hedged(&mut self, hedge_after: Duration) -> Result<(), E> where F: AsyncFn() -> Result<T, E> {
+        if !self.throttle().await {
+            return Ok(())
+        }
+        self.last_refresh_reason = Some(RefreshReason::Manual);
+        let started = clock::now();
+        let (result, calls, errors) = {
+            let refresh_fn = &self.refresh_fn;
+            let mut primary = std::pin::pin!(refresh_fn());
+            match tokio::time::timeout(hedge_after, primary.as_mut()).await {
+                Ok(result) => {
+                    let errors = u64::from(result.is_err());
+                    (result, 1, errors)
+                },
+                Err(_) => {
+                    let mut calls = [(primary, false), (std::pin::pin!(refresh_fn()), false)];
+                    let mut errors = 0;
+                    let result = std::future::poll_fn(|cx| {
+                        for (call, done) in calls.iter_mut().filter(|(_, done)| !*done) {
+                            match call.as_mut().poll(cx) {
+                                Poll::Ready(Ok(obj)) => return Poll::Ready(Ok(obj)),
+                                Poll::Ready(Err(e)) if errors == 1 => return Poll::Ready(Err(e)),
+                                Poll::Ready(Err(_)) => {
+                                    *done = true;
+                                    errors += 1;
+                                },
+                                Poll::Pending => ()
+                            }
+                        }
+                        Poll::Pending
+                    }).await;
+                    (result.inspect_err(|_| errors += 1), 2, errors)
+                }
+            }
+        };
+        self.refresh_count += calls;
+        for _ in 0..errors {
+            self.record_error(started);
+        }
+        let result = match result {
+            Ok(obj) => apply_post_transform(self.post_transform.as_ref(), obj).await.inspect_err(|_| self.record_error(started)),
+            Err(e) => Err(e)
+        };
+        self.obj = result?;
+        self.record_success(started);
+        self.mark_updated();
+        Ok(())
+    }
+    /// Read current cached value or refresh the value if it is already expired then
+    /// return the new value.
+    pub async fn get_or_refresh(&mut self) -> Result<&T, E> {
+        if let Some(reason) = self.lookup() {
+            self.refresh_for(reason).await?;
+        }
+        Ok(&self.obj)
+    }
+    /// Same as [Object::get_or_refresh()] but the value is returned as [Sliding] which call [Object::touch()]
+    /// when it is dropped. Reading the value this way extend its life so it only expire once it is unused for `ttl`.
+    /// Other read methods keep the fixed expiry.
+    pub async fn get_or_refresh_sliding(&mut self) -> Result<Sliding<'_, T, F, E>, E> {
+        self.get_or_refresh().await?;
+        Ok(Sliding::new(self))
+    }
+    /// Same as [Object::get_or_refresh()] so async code can read naturally as `cache.value().await`.
+    /// The returned value borrow the cache mutably, like [Object::get_or_refresh()], so the cache can't be
+    /// used again until the value is dropped. Copy or clone the value to keep it longer.
+    /// `IntoFuture` isn't implemented because its future type can't be named without boxing it.
+    /// ```rust
+    /// use generic_cache::Object;
+    ///
+    /// # tokio_test::block_on(async {
+    /// let mut cached = Object::new_lazy(std::time::Duration::from_secs(1), async || {Ok::<u16, ()>(200)});
+    /// let value = *cached.value().await.unwrap();
+    /// assert_eq!(value, 200);
+    /// # })
+    /// ```
+    pub async fn value(&mut self) -> Result<&T, E> {
+        self.get_or_refresh().await
+    }
+    /// Same as [Object::get_or_refresh()] but for synchronous caller. When a refresh is needed,
+    /// the current thread is blocked by [tokio::runtime::Handle::block_on] until it is done.
+    /// On a current thread runtime, the timer and IO of the handle are only driven while the runtime itself
+    /// is running `block_on` so a `refresh_fn` that wait on them never complete. Use a multi thread runtime.
+    ///
+    /// # Panics
+    /// It panic if it is called within an async context, e.g. inside a tokio task. Use
+    /// [Object::get_or_refresh()] there instead.
+    ///
+    /// Require `tokio` feature.
+    #[cfg(feature = "tokio")]
+    pub fn get_or_refresh_blocking(&mut self, handle: &tokio::runtime::Handle) -> Result<&T, E> {
+        handle.block_on(self.get_or_refresh())
+    }
+    /// Same as [Object::get_or_refresh()] but also return how long the refresh took.
+    /// The duration is `None` on a cache hit or `Some` when a refresh was run.
+    pub async fn get_or_refresh_timed(&mut self) -> Result<(&T, Option<Duration>), E> {
+        let mut latency = None;
+        if let Some(reason) = self.lookup() {
+            let start = clock::now();
+            self.refresh_for(reason).await?;
+            latency = Some(clock::elapsed(start));
+        }
+        Ok((&self.obj, latency))
+    }
+    /// Same as [Object::get_or_refresh()] but the value is returned as [CacheValue::Borrowed] so the code
+    /// can be shared with [SharedCache::get_or_refresh_value()].
+    pub async fn get_or_refresh_value(&mut self) -> Result<CacheValue<'_, T>, E> {
+        self.get_or_refresh().await.map(CacheValue::Borrowed)
+    }
+    /// Same as [Object::get_or_refresh()] but when the refresh fail and there's no usable value, `fallback`
+    /// is stored as the new value and returned instead of the error. The value given to the constructor
+    /// isn't usable, e.g. the default of [Object::new()], but a value that was set or refreshed afterward
+    /// is served stale instead of the fallback, within [Object::with_max_stale()] if it is set.
+    /// The stored fallback is valid for full `ttl` like a refreshed value.
+    /// The error is only counted in [CacheStats::errors].
+    pub async fn get_or_refresh_or(&mut self, fallback: T) -> &T {
+        if let Some(reason) = self.lookup() {
+            if self.refresh_for(reason).await.is_err() && (self.placeholder || !self.can_serve_stale()) {
+                self.set(fallback);
+            }
+        }
+        &self.obj
+    }
+    /// Same as [Object::get_or_refresh()] but when the cached value is not expired yet, it is passed to
+    /// async `validator` first. If `validator` return `false`, the value is refreshed despite the `ttl`.
+    /// This is useful when validity check need I/O, e.g. confirming that a token isn't revoked.
+    /// Error from `validator` is returned as is.
+    pub async fn get_or_refresh_validated(&mut self, validator: impl AsyncFn(&T) -> Result<bool, E>) -> Result<&T, E> {
+        let reason = match self.refresh_reason() {
+            None if !validator(&self.obj).await? => Some(RefreshReason::ValidatorRejected),
+            reason => reason
+        };
+        self.record_lookup(reason.is_none());
+        if let Some(reason) = reason {
+            self.refresh_for(reason).await?;
+        }
+        Ok(&self.obj)
+    }
+    /// Same as [Object::refresh()] but the refreshed value is passed to async `validator` before it is cached,
+    /// e.g. a schema check for data quality gating. If `validator` return `false`, the refreshed value is
+    /// dropped, the previous value and its last update time are kept and the rejection is counted in
+    /// [CacheStats::rejected_refreshes]. Return whether the refreshed value was accepted.
+    pub async fn refresh_validated(&mut self, validator: impl AsyncFn(&T) -> Result<bool, E>) -> Result<bool, E> {
+        let Some(obj) = self.call_refresh_fn(RefreshReason::Manual).await? else {
+            return Ok(false)
+        };
+        if !validator(&obj).await? {
+            self.rejected_count += 1;
+            return Ok(false)
+        }
+        self.obj = obj;
+        self.mark_updated();
+        Ok(true)
+    }
+    /// Limit how old a stale value returned by [Object::get_or_refresh_or_stale()] may be.
+    /// Stale value is only returned if its age is at most `ttl + max_stale`. Beyond that, the error is returned.
+    pub fn with_max_stale(mut self, max_stale: Duration) -> Self {
+        self.max_stale = Some(max_stale);
+        self
+    }
+    /// Same as [Object::get_or_refresh()] but when refresh fail, `classify` decide with [ErrorAction]
+    /// whether the stale value is returned, evicted or kept while the error is returned.
+    /// If there's no value to keep because it was evicted or it is older than allowed by
+    /// [Object::with_max_stale()], the error is returned.
+    pub async fn get_or_refresh_or_stale(&mut self, classify: impl Fn(&E) -> ErrorAction) -> Result<&T, E> {
+        let Some(reason) = self.lookup() else {
+            return Ok(&self.obj)
+        };
+        match self.refresh_for(reason).await {
+            Ok(()) => Ok(&self.obj),
+            Err(e) => match classify(&e) {
+                ErrorAction::KeepStale if self.can_serve_stale() => Ok(&self.obj),
+                ErrorAction::Evict => {
+                    self.loaded = false;
+                    Err(e)
+                },
+                _ => Err(e)
+            }
+        }
+    }
+    /// Cache a failed refresh for `error_ttl` when using [Object::get_or_refresh_negative()].
+    /// This is usually shorter than `ttl` so repeated failure isn't retried too quickly.
+    pub fn with_error_ttl(mut self, error_ttl: Duration) -> Self {
+        self.error_ttl = Some(error_ttl);
+        self
+    }
+    /// Same as [Object::get_or_refresh()] but a failed refresh is cached for the duration set by
+    /// [Object::with_error_ttl()]. Within that period, the cached error is returned again without
+    /// calling `refresh_fn`. Without `error_ttl`, this behave exactly like [Object::get_or_refresh()].
+    pub async fn get_or_refresh_negative(&mut self) -> Result<&T, E> where E: Clone {
+        let Some(reason) = self.lookup() else {
+            return Ok(&self.obj)
+        };
+        if let (Some(error_ttl), Some((e, failed_at))) = (self.error_ttl, &self.failure) {
+            if clock::elapsed(*failed_at) < error_ttl {
+                return Err(e.clone())
+            }
+        }
+        if let Err(e) = self.refresh_for(reason).await {
+            self.failure = Some((e.clone(), clock::now()));
+            return Err(e)
+        }
+        Ok(&self.obj)
+    }
+    /// Transform this cache into a cache of `U`. The currently cached value is transformed immediately
+    /// and every value returned by `refresh_fn` afterward is transformed by `f` before it is cached.
+    /// Other states, e.g. `ttl` and last update time, are kept as is. The transform of [Object::set_post_transform()]
+    /// is dropped because it take the previous type.
+    /// ```rust
+    /// use generic_cache::Object;
+    /// 
+    /// # tokio_test::block_on(async {
+    /// let mut cached = Object::new(std::time::Duration::from_secs(1), "1".to_string(), async || {Ok::<String, ()>("2".to_string())})
+    ///     .map_value(|raw| raw.parse::<u8>().unwrap());
+    /// assert_eq!(*cached.get().unwrap(), 1);
+    /// cached.refresh().await.unwrap();
+    /// assert_eq!(*cached.get().unwrap(), 2);
+    /// # })
+    /// ```
+    pub fn map_value<U>(self, f: impl Fn(T) -> U) -> Object<U, impl AsyncFnMut() -> Result<U, E>, E> {
+        self.map_parts(move |obj, mut refresh_fn| (f(obj), async move || refresh_fn().await.map(&f)))
+    }
+    /// Same as [Object::map_value()] but the transform `f` is async and it may fail, e.g. decrypting the
+    /// value with a remote key management service. `f` is applied to every value returned by `refresh_fn`
+    /// before it is cached. Error from `f` fail the refresh like error from `refresh_fn` does.
+    /// The currently cached value is kept as is because it can't be transformed without awaiting.
+    /// The transform of [Object::set_post_transform()], if any, is kept and applied after `f`.
+    /// ```rust
+    /// use generic_cache::Object;
+    ///
+    /// # tokio_test::block_on(async {
+    /// let mut cached = Object::new(std::time::Duration::from_secs(1), 0, async || {Ok::<u16, ()>(2)})
+    ///     .map_async(async |v| Ok(v * 100));
+    /// cached.refresh().await.unwrap();
+    /// assert_eq!(*cached.get().unwrap(), 200);
+    /// # })
+    /// ```
+    pub fn map_async(mut self, f: impl AsyncFn(T) -> Result<T, E>) -> Object<T, impl AsyncFnMut() -> Result<T, E>, E> {
+        let post_transform = self.post_transform.take();
+        let mut mapped = self.map_parts(|obj, mut refresh_fn| (obj, async move || f(refresh_fn().await?).await));
+        mapped.post_transform = post_transform;
+        mapped
+    }
+    /// Install an async transform `f` that is applied to every value returned by `refresh_fn`, on every
+    /// refresh path, before it is cached. Unlike [Object::map_async()], it is set on the existing object
+    /// so the type of the object stay the same. Error from `f` fail the refresh like error from `refresh_fn` does.
+    /// Setting it again replace the previous transform. The currently cached value is kept as is.
+    /// The transform is kept by [Object::map_async()] but it is dropped by [Object::map_value()], which change
+    /// the type of the value, and by [Object::into_shared()] because [SharedCache] has no transform.
+    /// ```rust
+    /// use generic_cache::Object;
+    ///
+    /// # tokio_test::block_on(async {
+    /// let mut cached = Object::new(std::time::Duration::from_secs(1), 0, async || {Ok::<u16, ()>(2)});
+    /// cached.set_post_transform(async |v| Ok(v * 100));
+    /// cached.refresh().await.unwrap();
+    /// assert_eq!(*cached.get().unwrap(), 200);
+    /// # })
+    /// ```
+    ///
+    /// The transform is stored in the object so its future must be [Send] and it can't borrow the value.
+    pub fn set_post_transform<Fut>(&mut self, f: impl Fn(T) -> Fut + Send + Sync + 'static) where Fut: Future<Output = Result<T, E>> + Send + 'static {
+        self.post_transform = Some(Arc::new(move |obj| Box::pin(f(obj))));
+    }
+    /// Convert this cache into a thread safe [SharedCache] that continue with the same `ttl`,
+    /// cached value, expiry of the cached value and refresh count. This allow building the cache with [Object]
+    /// then share it between tasks without re-specifying its `ttl` and `refresh_fn`.
+    /// 
+    /// [SharedCache] only has a plain `ttl` so the current value expire when it would have expired in this cache,
+    /// at the soft `ttl` of [Object::new_tiered()], but every later value is valid for full `ttl`.
+    /// An evicted or invalidated value is converted as expired. Every other setting is dropped:
+    /// - a paused cache is resumed first,
+    /// - the hard `ttl` of [Object::new_tiered()] so the stale value is never served,
+    /// - [Object::with_ttl_jitter()], [Object::with_ttl_bounds()], [Object::with_schedule()], [Object::with_max_lifetime()]
+    ///   and a deadline of [Object::force_expire_at()] after the current value is replaced,
+    /// - [Object::with_max_stale()], [Object::with_error_ttl()] with the cached error and the retry backoff,
+    /// - [Object::with_min_refresh_interval()] and [Object::with_refresh_at_most_every()],
+    /// - the transform of [Object::set_post_transform()],
+    /// - the version of [Object::replace_if_newer()] and every statistic except the refresh count.
+    ///
+    /// The `refresh_fn` must be a function returning `Future` because [SharedCache] call it through a shared reference.
+    /// A `refresh_fn` built by a constructor, e.g. a fallback chain or a transform, is kept as is.
+    pub fn into_shared<Fut>(mut self) -> SharedCache<T, F, E> where F: Fn() -> Fut, Fut: Future<Output = Result<T, E>> {
+        self.resume();
+        let remain = self.ttl_remaining_or_zero();
+        let last_update = if remain.is_zero() {
+            SystemTime::UNIX_EPOCH
+        } else {
+            clock::now().checked_sub(self.ttl.saturating_sub(remain)).unwrap_or(SystemTime::UNIX_EPOCH)
+        };
+        SharedCache::from_parts(self.ttl, self.obj, last_update, self.refresh_fn, self.refresh_count)
+    }
+    /// Turn this cache into a [futures_core::Stream] which refresh the value on every `interval` tick
+    /// and yield the refreshed value. The first tick is immediate.
+    /// A failed refresh is yielded as `Err` and the stream continue on the next tick. The stream never end.
+    /// 
+    /// Require `stream` feature and it must be polled within a tokio runtime with time enabled.
+    #[cfg(feature = "stream")]
+    pub fn into_stream(self, interval: Duration) -> impl futures_core::Stream<Item = Result<T, E>> + Unpin where T: Clone {
+        stream::Unfold::new((self, tokio::time::interval(interval)), |(mut cached, mut interval)| async move {
+            interval.tick().await;
+            let item = cached.refresh().await.map(|_| cached.obj.clone());
+            (item, (cached, interval))
+        })
+    }
+    /// Make the cached value expire after `remaining` from now while `ttl` stay unchanged.
+    /// If `remaining` is longer than `ttl`, reduced by the jitter of [Object::with_ttl_jitter()] if any, it is clamped to `ttl`.
+    /// The next refresh will use full `ttl` again.
+    pub fn expire_in(&mut self, remaining: Duration) {
+        let ttl = self.effective_ttl();
+        let remaining = remaining.min(ttl);
+        self.last_update = clock::now().checked_sub(ttl - remaining).unwrap_or(SystemTime::UNIX_EPOCH);
+    }
+}
+/// Backend that may fail but still return a usable partial value, e.g. a degraded response, can return
+/// the partial value along with the error.
+impl<T, F, E> Object<T, F, (E, Option<T>)> where F: AsyncFnMut() -> Result<T, (E, Option<T>)> {
+    /// Same as [Object::refresh()] but when `refresh_fn` fail with a partial value, the partial value is
+    /// cached for only `partial_ttl` so it is refreshed again sooner. The error is still returned.
+    /// If there's no partial value, the previous value is kept as usual.
+    /// `partial_ttl` longer than `ttl` is clamped to `ttl`.
+    pub async fn refresh_or_partial(&mut self, partial_ttl: Duration) -> Result<(), E> {
+        match self.call_refresh_fn(RefreshReason::Manual).await {
+            Ok(Some(obj)) => self.set(obj),
+            Ok(None) => (),
+            Err((e, partial)) => {
+                if let Some(partial) = partial {
+                    self.set(partial);
+                    self.expire_in(partial_ttl);
+                }
+                return Err(e)
+            }
+        }
+        Ok(())
+    }
+}
+/// Backend that is still provisioning data can answer "not ready yet" with [Poll::Pending] instead of an error.
+/// ```rust
+/// use std::task::Poll;
+/// use generic_cache::Object;
+///
+/// # tokio_test::block_on(async {
+/// let mut cached = Object::new_lazy_polling(std::time::Duration::from_secs(1), async || {Ok::<Poll<u16>, ()>(Poll::Ready(200))});
+/// assert_eq!(cached.get_or_refresh_polling(std::time::Duration::from_secs(1)).await, Ok(Poll::Ready(&200)));
+/// # })
+/// ```
+impl<T, F, E> Object<T, F, E> where F: AsyncFnMut() -> Result<Poll<T>, E> {
+    /// Same as [Object::new()] but `refresh_fn` return [Poll::Pending] when the value isn't ready yet.
+    pub fn new_polling(ttl: Duration, obj: T, refresh_fn: F) -> Object<T, F, E> {
+        Object::init(ttl, obj, refresh_fn)
+    }
+    /// Same as [Object::new_lazy()] but `refresh_fn` return [Poll::Pending] when the value isn't ready yet.
+    /// The cache stay unloaded until `refresh_fn` return [Poll::Ready].
+    pub fn new_lazy_polling(ttl: Duration, refresh_fn: F) -> Object<T, F, E> where T: Default {
+        let mut obj = Object::init(ttl, T::default(), refresh_fn);
+        obj.loaded = false;
+        obj
+    }
+    /// Same as [Object::get_or_refresh()] but when `refresh_fn` return [Poll::Pending], the previous value is kept,
+    /// or the cache stay unloaded, and [Poll::Pending] is returned. `refresh_fn` isn't called again until
+    /// `retry_after` elapsed so a backend that is not ready isn't polled too often. Meanwhile, [Poll::Pending]
+    /// is returned right away. Unlike an error, a pending result isn't counted in [CacheStats::errors].
+    /// The previous value stay readable by [Object::peek()].
+    pub async fn get_or_refresh_polling(&mut self, retry_after: Duration) -> Result<Poll<&T>, E> {
+        let Some(reason) = self.lookup() else {
+            return Ok(Poll::Ready(&self.obj))
+        };
+        if self.retry_at.is_some_and(|retry_at| clock::now() < retry_at) || !self.throttle().await {
+            return Ok(Poll::Pending)
+        }
+        self.refresh_count += 1;
+        self.last_refresh_reason = Some(reason);
+        let started = clock::now();
+        match (self.refresh_fn)().await.inspect_err(|_| self.record_error(started))? {
+            Poll::Ready(obj) => {
+                self.obj = apply_post_transform(self.post_transform.as_ref(), obj).await.inspect_err(|_| self.record_error(started))?;
+                self.record_success(started);
+                self.mark_updated();
+                Ok(Poll::Ready(&self.obj))
+            },
+            Poll::Pending => {
+                self.retry_at = Some(clock::now() + retry_after);
+                Ok(Poll::Pending)
+            }
+        }
+    }
+}
+/// `refresh_fn` that block the thread, e.g. CPU heavy work or blocking IO, shouldn't run on the async executor.
+///
+/// Require `tokio` feature.
+#[cfg(feature = "tokio")]
+impl<T, E> Object<T, (), E> where T: Send + 'static, E: Send + 'static {
+    /// Same as [Object::new()] but `refresh_fn` is a blocking function. Each refresh run it on tokio blocking
+    /// thread pool by [tokio::task::spawn_blocking] so the async runtime stay responsive.
+    /// `refresh_fn` is called once per refresh so it must be [Fn] rather than [FnOnce].
+    /// If `refresh_fn` panic, the panic is resumed on the task awaiting the refresh.
+    /// ```rust
+    /// use generic_cache::Object;
+    ///
+    /// # tokio_test::block_on(async {
+    /// let mut cached = Object::new_blocking(std::time::Duration::from_secs(1), 100, || {Ok::<u16, ()>(200)});
+    /// cached.refresh().await.unwrap();
+    /// assert_eq!(*cached.get().unwrap(), 200);
+    /// # })
+    /// ```
+    pub fn new_blocking(ttl: Duration, obj: T, refresh_fn: impl Fn() -> Result<T, E> + Send + Sync + 'static) -> Object<T, impl AsyncFnMut() -> Result<T, E>, E> {
+        let refresh_fn = Arc::new(refresh_fn);
+        Object::init(ttl, obj, async move || {
+            let refresh_fn = Arc::clone(&refresh_fn);
+            tokio::task::spawn_blocking(move || refresh_fn()).await.unwrap_or_else(|e| std::panic::resume_unwind(e.into_panic()))
+        })
+    }
+}
+/// A value that never change can be cached as a permanent [Object] so it is handled the same way as
+/// the refreshed one, e.g. in test or when a value is only sometimes loaded from a backend.
+/// ```rust
+/// use generic_cache::Object;
+///
+/// # tokio_test::block_on(async {
+/// let mut cached: Object<_, _, ()> = Object::constant(100);
+/// assert_eq!(*cached.get().unwrap(), 100);
+/// assert_eq!(cached.get_or_refresh().await, Ok(&100));
+/// # })
+/// ```
+impl<T, E> Object<T, (), E> where T: Clone {
+    /// Create a cached Object that never expire. Its `refresh_fn` return a clone of `obj`
+    /// so an explicit refresh keep the same value.
+    pub fn constant(obj: T) -> Object<T, impl AsyncFnMut() -> Result<T, E>, E> {
+        let value = obj.clone();
+        Object::init(Duration::MAX, obj, async move || Ok(value.clone()))
+    }
+}
+/// The fetched raw value can be converted before it is cached, e.g. parsed from a response body,
+/// so the type returned by the backend doesn't have to be the cached type.
+/// ```rust
+/// use generic_cache::Object;
+///
+/// # tokio_test::block_on(async {
+/// let mut cached = Object::new_with_transform(std::time::Duration::from_secs(1), 0, async || {Ok::<&str, ()>("200")}, |raw| raw.parse::<u16>().unwrap());
+/// cached.refresh().await.unwrap();
+/// assert_eq!(*cached.get().unwrap(), 200);
+/// # })
+/// ```
+impl<T, E> Object<T, (), E> {
+    /// Create a new cached Object with default value specify in second argument whose `refresh_fn`
+    /// return a raw value that `transform` convert into the cached value on every refresh.
+    pub fn new_with_transform<R>(ttl: Duration, obj: T, mut refresh_fn: impl AsyncFnMut() -> Result<R, E>, transform: impl Fn(R) -> T) -> Object<T, impl AsyncFnMut() -> Result<T, E>, E> {
+        Object::init(ttl, obj, async move || refresh_fn().await.map(&transform))
+    }
+}
+/// Backend may return metadata, e.g. generation number, source node or response headers, along with
+/// the value. The value is cached as [WithMetadata] so the latest metadata stay accessible without
+/// being part of `T`. The metadata is `None` until the first refresh.
+/// ```rust
+/// use generic_cache::Object;
+///
+/// # tokio_test::block_on(async {
+/// let mut cached = Object::new_with_metadata(std::time::Duration::from_secs(1), 100, async || {Ok::<(u16, &str), ()>((200, "node-1"))});
+/// cached.refresh().await.unwrap();
+/// assert_eq!(*cached.get_value().unwrap(), 200);
+/// assert_eq!(cached.metadata(), Some(&"node-1"));
+/// # })
+/// ```
+impl<T, M, E> Object<WithMetadata<T, M>, (), E> {
+    /// Create a new cached Object with default value specify in second argument and no metadata.
+    /// `refresh_fn` return the new value along with its metadata.
+    #[allow(clippy::type_complexity, reason = "the returned refresh_fn type can't be named")]
+    pub fn new_with_metadata(ttl: Duration, obj: T, mut refresh_fn: impl AsyncFnMut() -> Result<(T, M), E>) -> Object<WithMetadata<T, M>, impl AsyncFnMut() -> Result<WithMetadata<T, M>, E>, E> {
+        let obj = WithMetadata { value: obj, metadata: None };
+        Object::init(ttl, obj, async move || refresh_fn().await.map(|(value, metadata)| WithMetadata { value, metadata: Some(metadata) }))
+    }
+}
+impl<T, M, F, E> Object<WithMetadata<T, M>, F, E> {
+    /// Read current cached value without its metadata or return Error if cache is already expired.
+    pub fn get_value(&self) -> Result<&T, TimeoutError> {
+        self.get().map(WithMetadata::value)
+    }
+    /// Metadata returned by the last successful refresh, even if the value is already expired.
+    pub fn metadata(&self) -> Option<&M> {
+        self.obj.metadata()
+    }
+}
+/// Redundant backends can be tried in order. The index of the source that loaded the value is kept
+/// in its own field of [WithMetadata], see [Object::refresh_source()].
+/// ```rust
+/// use generic_cache::{Object, RefreshSource};
+///
+/// # tokio_test::block_on(async {
+/// let primary: RefreshSource<u16, &str> = Box::new(|| Box::pin(async {Err("down")}));
+/// let secondary: RefreshSource<u16, &str> = Box::new(|| Box::pin(async {Ok(200)}));
+/// let mut cached = Object::with_fallback_chain(std::time::Duration::from_secs(1), 100, vec![primary, secondary]);
+/// cached.refresh().await.unwrap();
+/// assert_eq!(*cached.get_value().unwrap(), 200);
+/// assert_eq!(cached.refresh_source(), Some(1));
+/// # })
+/// ```
+impl<T, E> Object<WithMetadata<T, usize>, (), E> {
+    /// Create a new cached Object with default value specify in second argument whose refresh try each of
+    /// `sources` in order until one success. If every source fail, the error of the last one is returned.
+    /// The index in `sources` of the source that loaded the value is returned by [Object::refresh_source()].
+    ///
+    /// # Panics
+    /// It panic if `sources` is empty.
+    #[allow(clippy::type_complexity, reason = "the returned refresh_fn type can't be named")]
+    pub fn with_fallback_chain<'a>(ttl: Duration, obj: T, mut sources: Vec<RefreshSource<'a, T, E>>) -> Object<WithMetadata<T, usize>, impl AsyncFnMut() -> Result<WithMetadata<T, usize>, E> + 'a, E> where T: 'a, E: 'a {
+        assert!(!sources.is_empty(), "with_fallback_chain need at least one source");
+        Object::init(ttl, WithMetadata { value: obj, metadata: None }, async move || {
+            let mut source = 0;
+            loop {
+                match sources[source]().await {
+                    Ok(value) => return Ok(WithMetadata { value, metadata: Some(source) }),
+                    Err(e) if source + 1 == sources.len() => return Err(e),
+                    Err(_) => source += 1
+                }
+            }
+        })
+    }
+}
+impl<T, F, E> Object<WithMetadata<T, usize>, F, E> {
+    /// Index of the source of [Object::with_fallback_chain()] that loaded the cached value or `None`
+    /// if it wasn't loaded by a refresh.
+    pub fn refresh_source(&self) -> Option<usize> {
+        self.metadata().copied()
+    }
+}
+/// A trait object can be cached as `Arc<dyn Trait>`, e.g. a plugin whose implementation change on refresh.
+/// `refresh_fn` box the new implementation and the box is moved into a new [Arc].
+/// ```rust
+/// use std::sync::Arc;
+/// use generic_cache::Object;
+///
+/// trait Greeter: Send + Sync {
+///     fn greet(&self) -> String;
+/// }
+/// struct English;
+/// impl Greeter for English {
+///     fn greet(&self) -> String { "hello".to_owned() }
+/// }
+/// struct Thai;
+/// impl Greeter for Thai {
+///     fn greet(&self) -> String { "sawasdee".to_owned() }
+/// }
+///
+/// # tokio_test::block_on(async {
+/// let initial: Arc<dyn Greeter> = Arc::new(English);
+/// let mut cached = Object::new_boxed(std::time::Duration::from_secs(1), initial, async || {Ok::<Box<dyn Greeter>, ()>(Box::new(Thai))});
+/// assert_eq!(cached.get().unwrap().greet(), "hello");
+/// cached.refresh().await.unwrap();
+/// assert_eq!(cached.get().unwrap().greet(), "sawasdee");
+/// # })
+/// ```
+impl<T: ?Sized, E> Object<Arc<T>, (), E> {
+    /// Create a new cached Object with default value specify in second argument whose `refresh_fn`
+    /// return a [Box], which may hold a different concrete type each time when `T` is a trait object.
+    #[allow(clippy::type_complexity, reason = "the returned refresh_fn type can't be named")]
+    pub fn new_boxed(ttl: Duration, obj: Arc<T>, mut refresh_fn: impl AsyncFnMut() -> Result<Box<T>, E>) -> Object<Arc<T>, impl AsyncFnMut() -> Result<Arc<T>, E>, E> {
+        Object::init(ttl, obj, async move || refresh_fn().await.map(Arc::from))
+    }
+}
+/// Large value can be cached as [Arc] so that caller can hold an owned handle without deep copy.
+/// Refresh replace the whole [Arc] so the handle returned before refresh keep pointing to the previous value.
+impl<T: ?Sized, F, E> Object<Arc<T>, F, E> where F: AsyncFnMut() -> Result<Arc<T>, E> {
+    /// Same as [Object::get_or_refresh()] but return a cheap clone of the cached [Arc].
+    pub async fn get_or_refresh_arc(&mut self) -> Result<Arc<T>, E> {
+        self.get_or_refresh().await.map(Arc::clone)
+    }
+}
+/// Binary payload cached as [bytes::Bytes] can be handed out without copying the underlying buffer.
+///
+/// Require `bytes` feature.
+#[cfg(feature = "bytes")]
+impl<F, E> Object<bytes::Bytes, F, E> {
+    /// Same as [Object::get()] but return a cheap clone of the cached [bytes::Bytes] which share
+    /// the same buffer instead of a reference.
+    pub fn get_bytes_clone(&self) -> Result<bytes::Bytes, TimeoutError> {
+        self.get().cloned()
+    }
+}
+#[cfg(test)]
+mod tests {
+    use core::time;
+    use std::{thread::sleep, time::Duration};
+
+    use super::*;
+
+    #[test]
+    fn simple_cache() {
+        let cached = Object::new(Duration::from_secs(1), 100, async || {Ok::<u16, ()>(200)});
+        let first = cached.get().unwrap();
+        let second = cached.get().unwrap();
+        assert_eq!(*first, 100, "Expect {} to equals {}", *first, 0);
+        assert_eq!(first, second, "Expect {} to equals {}", first, second);
+    }
+    #[tokio::test]
+    async fn simple_refresh() {
+        let mut cached = Object::new(Duration::from_secs(1), 100, async || {Ok::<u16, ()>(200)});
+        let first = *cached.get().unwrap();
+        cached.refresh().await.unwrap();
+        let second = *cached.get().unwrap();
+        assert_eq!(first, 100, "Expect {} to equals {}", first, 100);
+        assert_eq!(second, 200, "Expect {} to equals {}", first, 200);
+    }
+    #[tokio::test]
+    async fn simple_no_cache() {
+        let mut cached = Object::new(Duration::ZERO, 100, async || {Ok::<u16, ()>(200)});
+        let first = *cached.get_or_refresh().await.unwrap();
+        sleep(time::Duration::from_millis(1));
+        let second = *cached.get_or_refresh().await.unwrap();
+        assert_eq!(first, 200, "Expect {} to equals {}", first, 200);
+        assert_eq!(first, second, "Expect {} to equals {}", first, second);
+    }
+    #[tokio::test]
+    async fn simple_expire_check() {
+        let mut cached = Object::new(Duration::from_millis(1), 100, async || {Ok::<u16, ()>(200)});
+        let first = *cached.get().unwrap();
+        sleep(time::Duration::from_millis(1));
+        if cached.get().is_ok() {
+            panic!("Cache should be expired but it is not.")
+        } else {
+            cached.refresh().await.unwrap();
+        }
+        let second = *cached.get().unwrap();
+        assert_ne!(first, second, "Expect {} to equals {}", first, second);
+    }
+    #[tokio::test]
+    async fn immediate_refresh() {
+        let mut cached = Object::new_and_refresh(Duration::from_secs(1), async || {Ok::<u16, ()>(200)}).await.unwrap();
+        let first = *cached.get_or_refresh().await.unwrap();
+        let second = *cached.get_or_refresh().await.unwrap();
+        assert_eq!(first, second, "Expect {} to equals {}", first, second);
+    }
+    #[tokio::test]
+    async fn time_remain_validate() {
+        let mut cached = Object::new(Duration::from_secs(5), 100, async || {Ok::<u16, ()>(200)});
+        sleep(Duration::from_secs(1));
+        let original_remain = cached.time_remain();
+        println!("Original time remain is {:?}", original_remain);
+        cached.refresh().await.unwrap();
+        let new_remain = cached.time_remain();
+        println!("New time remain is {:?}", new_remain);
+        assert!(new_remain > original_remain, "Original time remain should be less than a fresh new value time remain.");
+    }
+    #[test]
+    fn simple_object() {
+        struct Dummy {
+            v: u8
+        }
+        let cached = Object::new(Duration::from_secs(1), Dummy {v: 1}, async || {Ok::<Dummy, ()>(Dummy {v: 2})});
+        let Dummy { v: v1} = cached.get().unwrap();
+        let Dummy { v: v2} = cached.get().unwrap();
+        assert_eq!(*v1, 1, "Expect {} to equals {}", v1, 1);
+        assert_eq!(v1, v2, "Expect {} to equals {}", v1, v2);
+    }
+    #[tokio::test]
+    async fn simple_object_mut() {
+        let mut count = 0u8;
+        struct Dummy {
+            v: u8
+        }
+        let mut cached = Object::new(Duration::from_secs(1), Dummy {v: 1}, async || {
+            count += 1;
+            Ok::<Dummy, ()>(Dummy {v: 2})
+        });
+        let Dummy { v: v1} = cached.get().unwrap();
+        let Dummy { v: v2} = cached.get().unwrap();
+        let original = *v1;
+        assert_eq!(*v1, 1, "Expect {} to equals {}", v1, 1);
+        assert_eq!(*v1, *v2, "Expect {} to equals {}", v1, v2);
+        cached.refresh().await.expect("to get a new value");
+        let Dummy { v: new_v} = cached.get().unwrap();
+        assert_ne!(original, *new_v, "Expect to get a new value");
         assert_eq!(count, 1, "counter should be 1");
     }
+    #[test]
+    fn expire_in_validate() {
+        let mut cached = Object::new(Duration::from_secs(10), 100, async || {Ok::<u16, ()>(200)});
+        cached.expire_in(Duration::from_millis(50));
+        assert!(cached.time_remain() <= Duration::from_millis(50), "Time remain should be at most the given remaining time");
+        assert!(cached.get().is_ok(), "Cache should still be valid before the remaining time");
+        sleep(Duration::from_millis(60));
+        assert!(cached.get().is_err(), "Cache should be expired after the remaining time");
+        cached.expire_in(Duration::from_secs(20));
+        assert!(cached.time_remain() <= Duration::from_secs(10), "Remaining time should be clamped to ttl");
+    }
+    #[test]
+    fn hash_by_value() {
+        use std::collections::HashSet;
+
+        let refresh = async || {Ok::<u16, ()>(200)};
+        let mut set = HashSet::new();
+        assert!(set.insert(Object::new(Duration::from_secs(1), 100, refresh)), "First value should be inserted");
+        assert!(set.insert(Object::new(Duration::from_secs(2), 200, refresh)), "Different value should be inserted");
+        assert!(!set.insert(Object::new(Duration::from_secs(3), 100, refresh)), "Same value with different ttl should be considered duplicate");
+        assert_eq!(set.len(), 2, "Expect {} to equals {}", set.len(), 2);
+    }
+    #[test]
+    fn ttl_millis_round_trip() {
+        let mut cached = Object::new(Duration::from_secs(1), 100, async || {Ok::<u16, ()>(200)});
+        assert_eq!(cached.ttl_millis(), 1000, "Expect {} to equals {}", cached.ttl_millis(), 1000);
+        cached.set_ttl_millis(1500);
+        assert_eq!(cached.ttl_millis(), 1500, "Expect {} to equals {}", cached.ttl_millis(), 1500);
+        assert_eq!(cached.ttl(), Duration::from_millis(1500), "Expect {:?} to equals {:?}", cached.ttl(), Duration::from_millis(1500));
+        cached.set_ttl(Duration::from_secs(2));
+        assert_eq!(cached.ttl_millis(), 2000, "Expect {} to equals {}", cached.ttl_millis(), 2000);
+    }
+    #[tokio::test]
+    async fn async_validator() {
+        let mut cached = Object::new(Duration::from_secs(60), 100, async || {Ok::<u16, ()>(200)});
+        let valid = *cached.get_or_refresh_validated(async |v| Ok(*v == 100)).await.unwrap();
+        assert_eq!(valid, 100, "Expect {} to equals {}", valid, 100);
+        let revoked = *cached.get_or_refresh_validated(async |_| Ok(false)).await.unwrap();
+        assert_eq!(revoked, 200, "Expect validator to force a refresh despite the ttl");
+        assert!(cached.get_or_refresh_validated(async |_| Err(())).await.is_err(), "Expect validator error to be returned");
+    }
+    #[tokio::test]
+    async fn refresh_count_validate() {
+        let mut fail = false;
+        let mut cached = Object::new(Duration::from_secs(60), 100, async || {
+            if fail {
+                Err(())
+            } else {
+                fail = true;
+                Ok::<u16, ()>(200)
+            }
+        });
+        assert_eq!(cached.refresh_count(), 0, "Expect no refresh on new");
+        cached.refresh().await.unwrap();
+        assert_eq!(cached.refresh_count(), 1, "Expect {} to equals {}", cached.refresh_count(), 1);
+        cached.get_or_refresh().await.unwrap();
+        assert_eq!(cached.refresh_count(), 1, "Expect no refresh when cache is still valid");
+        assert!(cached.refresh().await.is_err(), "Expect second refresh to fail");
+        assert_eq!(cached.refresh_count(), 2, "Expect failed refresh to be counted");
+        let immediate = Object::new_and_refresh(Duration::from_secs(60), async || {Ok::<u16, ()>(200)}).await.unwrap();
+        assert_eq!(immediate.refresh_count(), 1, "Expect new_and_refresh to count the initial refresh");
+    }
+    #[tokio::test]
+    async fn arc_value() {
+        let mut cached = Object::new(Duration::from_secs(60), Arc::new(vec![0u8; 1 << 20]), async || {Ok::<Arc<Vec<u8>>, ()>(Arc::new(vec![1u8; 1 << 20]))});
+        let first = cached.get_or_refresh_arc().await.unwrap();
+        let second = cached.get_or_refresh_arc().await.unwrap();
+        assert!(Arc::ptr_eq(&first, &second), "Expect both handle to point to the same allocation");
+        cached.refresh().await.unwrap();
+        let third = cached.get_or_refresh_arc().await.unwrap();
+        assert!(!Arc::ptr_eq(&first, &third), "Expect refresh to replace the allocation");
+        assert_eq!(first[0], 0, "Expect previous handle to keep the previous value");
+        assert_eq!(third[0], 1, "Expect new handle to point to the refreshed value");
+    }
+    #[tokio::test]
+    async fn merge_refresh() {
+        let mut count = 0u8;
+        let mut cached = Object::new(Duration::from_secs(60), vec![0u8], async || {
+            count += 1;
+            Ok::<Vec<u8>, ()>(vec![count])
+        });
+        cached.refresh_merging(|old, new| old.extend(new)).await.unwrap();
+        cached.refresh_merging(|old, new| old.extend(new)).await.unwrap();
+        assert_eq!(cached.get().unwrap(), &vec![0, 1, 2], "Expect refreshed values to be accumulated");
+    }
+    #[tokio::test]
+    async fn pause_and_resume() {
+        let mut cached = Object::new(Duration::from_millis(50), 100, async || {Ok::<u16, ()>(200)});
+        cached.pause();
+        sleep(Duration::from_millis(80));
+        assert!(cached.get().is_ok(), "Paused cache should never expire");
+        let paused = *cached.get_or_refresh().await.unwrap();
+        assert_eq!(paused, 100, "Paused cache should not be refreshed");
+        assert_eq!(cached.refresh_count(), 0, "Expect no refresh while paused");
+        cached.resume();
+        assert!(cached.get().is_ok(), "Time remain before pause should be preserved");
+        sleep(Duration::from_millis(60));
+        assert!(cached.get().is_err(), "Cache should expire again once resumed");
+    }
+    #[tokio::test]
+    async fn into_inner_cleanup() {
+        struct Resource<'a> {
+            v: u8,
+            closed: &'a std::cell::Cell<Option<u8>>
+        }
+        impl Resource<'_> {
+            fn close(self) {
+                self.closed.set(Some(self.v));
+            }
+        }
+        let closed = std::cell::Cell::new(None);
+        let mut cached = Object::new(Duration::from_secs(1), Resource {v: 1, closed: &closed}, async || {Ok::<Resource, ()>(Resource {v: 2, closed: &closed})});
+        cached.refresh().await.unwrap();
+        cached.into_inner().close();
+        assert_eq!(closed.get(), Some(2), "Expect cleanup to run with the last value");
+    }
+    #[tokio::test]
+    async fn map_refreshed_value() {
+        let mut raw = 0u8;
+        let mut cached = Object::new(Duration::from_secs(1), "0".to_string(), async || {
+            raw += 21;
+            Ok::<String, ()>(raw.to_string())
+        }).map_value(|s| s.parse::<u16>().unwrap() * 2);
+        assert_eq!(*cached.get().unwrap(), 0, "Expect {} to equals {}", *cached.get().unwrap(), 0);
+        cached.refresh().await.unwrap();
+        assert_eq!(*cached.get().unwrap(), 42, "Expect {} to equals {}", *cached.get().unwrap(), 42);
+        cached.refresh().await.unwrap();
+        assert_eq!(*cached.get().unwrap(), 84, "Expect {} to equals {}", *cached.get().unwrap(), 84);
+    }
+    #[tokio::test]
+    async fn state_validate() {
+        let mut cached = Object::new(Duration::from_millis(20), 100, async || {Ok::<u16, ()>(200)});
+        assert!(matches!(cached.state(), CacheState::Valid { remaining } if remaining <= Duration::from_millis(20)), "Expect fresh cache to be valid but got {:?}", cached.state());
+        sleep(Duration::from_millis(30));
+        assert!(matches!(cached.state(), CacheState::Expired { age } if age >= Duration::from_millis(30)), "Expect cache to be expired but got {:?}", cached.state());
+        cached.refresh().await.unwrap();
+        assert!(matches!(cached.state(), CacheState::Valid { .. }), "Expect refreshed cache to be valid but got {:?}", cached.state());
+    }
+    #[test]
+    fn get_at_validate() {
+        let cached = Object::new(Duration::from_secs(60), 100, async || {Ok::<u16, ()>(200)});
+        let now = SystemTime::now();
+        assert!(cached.get_at(now + Duration::from_secs(30)).is_ok(), "Expect cache to be valid before ttl");
+        assert!(cached.get_at(now + Duration::from_secs(61)).is_err(), "Expect TimeoutError when now is past ttl");
+    }
+    #[tokio::test]
+    async fn classify_refresh_error() {
+        let mut responses = vec![503u16, 404, 503];
+        let mut cached = Object::new(Duration::ZERO, 100, async || {Err::<u16, u16>(responses.remove(0))});
+        let classify = |status: &u16| if *status == 404 { ErrorAction::Evict } else { ErrorAction::KeepStale };
+        sleep(Duration::from_millis(1));
+        assert_eq!(cached.get_or_refresh_or_stale(classify).await, Ok(&100), "Expect stale value to be kept on 503");
+        assert_eq!(cached.get_or_refresh_or_stale(classify).await, Err(404), "Expect error to be returned on 404");
+        assert!(cached.get().is_err(), "Expect evicted value to be unavailable");
+        assert_eq!(cached.state(), CacheState::NeverLoaded, "Expect {:?} to equals {:?}", cached.state(), CacheState::NeverLoaded);
+        assert_eq!(cached.get_or_refresh_or_stale(classify).await, Err(503), "Expect evicted value to never be returned as stale");
+    }
+    #[tokio::test]
+    async fn max_stale_validate() {
+        let mut cached = Object::new(Duration::from_millis(10), 100, async || {Err::<u16, ()>(())}).with_max_stale(Duration::from_millis(20));
+        sleep(Duration::from_millis(15));
+        assert_eq!(cached.get_or_refresh_or_stale(|_| ErrorAction::KeepStale).await, Ok(&100), "Expect stale value within max stale to be returned");
+        sleep(Duration::from_millis(20));
+        assert_eq!(cached.get_or_refresh_or_stale(|_| ErrorAction::KeepStale).await, Err(()), "Expect error once value is older than ttl + max stale");
+    }
+    #[tokio::test]
+    async fn into_shared_validate() {
+        let mut cached = Object::new(Duration::from_secs(60), 100, || async {Ok::<u16, ()>(200)});
+        assert_eq!(*cached.get_or_refresh().await.unwrap(), 100, "Expect initial value before conversion");
+        let shared = cached.into_shared();
+        let refresher = {
+            let shared = shared.clone();
+            tokio::spawn(async move { shared.refresh().await.unwrap() })
+        };
+        refresher.await.unwrap();
+        let reader = {
+            let shared = shared.clone();
+            tokio::spawn(async move { *shared.get().unwrap() })
+        };
+        assert_eq!(reader.await.unwrap(), 200, "Expect refresh from one task to be visible in another task");
+        assert_eq!(shared.refresh_count(), 1, "Expect refresh count to carry over");
+    }
+    #[test]
+    fn into_shared_expiry() {
+        let loader = || async {Ok::<u16, ()>(200)};
+        let mut cached = Object::new(Duration::from_secs(60), 100, loader);
+        cached.force_expire_at(clock::now() + Duration::from_secs(30));
+        let remaining = cached.into_shared().time_remain();
+        assert!(remaining <= Duration::from_secs(30) && remaining > Duration::from_secs(29), "Expect forced deadline to carry over but got {:?}", remaining);
+        let tiered = Object::new_tiered(Duration::ZERO, Duration::from_secs(60), 100, loader);
+        sleep(Duration::from_millis(1));
+        assert!(tiered.get().is_ok(), "Expect tiered value to be served between soft and hard ttl");
+        assert!(tiered.into_shared().get().is_err(), "Expect shared cache to expire the value at soft ttl");
+    }
+    #[tokio::test]
+    async fn skip_unchanged_refresh() {
+        let mut count = 0u8;
+        let mut cached = Object::new(Duration::from_secs(60), Arc::new(vec![0u8]), async || {
+            count += 1;
+            Ok::<Arc<Vec<u8>>, ()>(Arc::new(vec![count / 2]))
+        });
+        let first = cached.get_or_refresh_arc().await.unwrap();
+        cached.expire_in(Duration::ZERO);
+        assert!(!cached.refresh_if_changed().await.unwrap(), "Expect equal value to not be replaced");
+        assert!(cached.get().is_ok(), "Expect last update time to be refreshed");
+        assert!(Arc::ptr_eq(&first, &cached.get_or_refresh_arc().await.unwrap()), "Expect the original allocation to be kept");
+        assert!(cached.refresh_if_changed().await.unwrap(), "Expect different value to replace the cached value");
+        assert_eq!(*cached.get_or_refresh_arc().await.unwrap(), vec![1], "Expect new value to be cached");
+    }
+    #[tokio::test]
+    async fn refresh_reason_validate() {
+        let mut cached = Object::new(Duration::from_millis(10), 100, async || {Ok::<u16, ()>(200)});
+        assert_eq!(cached.last_refresh_reason(), None, "Expect no reason before any refresh");
+        cached.invalidate();
+        assert!(cached.get().is_err(), "Expect invalidated value to be expired");
+        assert_eq!(*cached.get_or_refresh().await.unwrap(), 200, "Expect invalidated value to be refreshed");
+        assert_eq!(cached.last_refresh_reason(), Some(RefreshReason::Invalidated), "Expect {:?} to equals {:?}", cached.last_refresh_reason(), Some(RefreshReason::Invalidated));
+        sleep(Duration::from_millis(15));
+        cached.get_or_refresh().await.unwrap();
+        assert_eq!(cached.last_refresh_reason(), Some(RefreshReason::Expired), "Expect {:?} to equals {:?}", cached.last_refresh_reason(), Some(RefreshReason::Expired));
+        cached.get_or_refresh_validated(async |_| Ok(false)).await.unwrap();
+        assert_eq!(cached.last_refresh_reason(), Some(RefreshReason::ValidatorRejected), "Expect {:?} to equals {:?}", cached.last_refresh_reason(), Some(RefreshReason::ValidatorRejected));
+        cached.refresh().await.unwrap();
+        assert_eq!(cached.last_refresh_reason(), Some(RefreshReason::Manual), "Expect {:?} to equals {:?}", cached.last_refresh_reason(), Some(RefreshReason::Manual));
+    }
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn startup_timeout() {
+        let slow = Object::try_new_and_refresh_with_timeout(Duration::from_secs(1), async || {
+            tokio::time::sleep(Duration::from_secs(1)).await;
+            Ok::<u16, ()>(200)
+        }, Duration::from_millis(50)).await;
+        assert!(matches!(slow, Err(RefreshError::Timeout(_))), "Expect slow refresh to time out but got {:?}", slow);
+        let fast = Object::try_new_and_refresh_with_timeout(Duration::from_secs(1), async || {Ok::<u16, ()>(200)}, Duration::from_millis(50)).await.unwrap();
+        assert_eq!(*fast.get().unwrap(), 200, "Expect {} to equals {}", *fast.get().unwrap(), 200);
+    }
+    #[tokio::test]
+    async fn negative_cache() {
+        let mut calls = 0u8;
+        let mut cached = Object::new(Duration::ZERO, 100, async || {
+            calls += 1;
+            if calls == 1 { Err::<u16, u8>(calls) } else { Ok(200) }
+        }).with_error_ttl(Duration::from_millis(30));
+        sleep(Duration::from_millis(1));
+        assert_eq!(cached.get_or_refresh_negative().await, Err(1), "Expect first refresh to fail");
+        assert_eq!(cached.get_or_refresh_negative().await, Err(1), "Expect failure to be cached within error ttl");
+        assert_eq!(cached.refresh_count(), 1, "Expect no retry within error ttl");
+        sleep(Duration::from_millis(40));
+        assert_eq!(cached.get_or_refresh_negative().await, Ok(&200), "Expect retry once error ttl elapsed");
+        assert_eq!(cached.refresh_count(), 2, "Expect {} to equals {}", cached.refresh_count(), 2);
+    }
+    #[cfg(feature = "tokio-clock")]
+    #[tokio::test(start_paused = true)]
+    async fn expired_future() {
+        let cached = Object::new(Duration::from_millis(50), 100, async || {Ok::<u16, ()>(200)});
+        let start = tokio::time::Instant::now();
+        cached.expired().await;
+        let elapsed = start.elapsed();
+        assert!(elapsed >= Duration::from_millis(50), "Expect future to resolve around ttl but it took {:?}", elapsed);
+        assert!(elapsed < Duration::from_millis(52), "Expect future to resolve around ttl but it took {:?}", elapsed);
+        tokio::time::advance(Duration::from_millis(1)).await;
+        assert!(cached.get().is_err(), "Expect cache to be expired once the future resolved");
+    }
+    #[cfg(feature = "tokio-clock")]
+    #[tokio::test(start_paused = true)]
+    async fn paused_tokio_clock() {
+        let mut cached = Object::new(Duration::from_secs(3600), 100, async || {Ok::<u16, ()>(200)});
+        tokio::time::advance(Duration::from_secs(3599)).await;
+        assert_eq!(*cached.get().unwrap(), 100, "Expect value to be valid before ttl elapsed");
+        tokio::time::advance(Duration::from_secs(2)).await;
+        assert!(cached.get().is_err(), "Expect advancing tokio clock to expire the value");
+        assert_eq!(*cached.get_or_refresh().await.unwrap(), 200, "Expect value to be refreshed once backend recover");
+    }
+    #[tokio::test]
+    async fn retry_with_backoff() {
+        let mut calls = 0u8;
+        let mut cached = Object::new(Duration::from_secs(1), 100, async || {
+            calls += 1;
+            if calls < 3 { Err::<u16, u8>(calls) } else { Ok(200) }
+        });
+        let policy = RetryPolicy { max_attempts: 5, base: Duration::from_millis(100), max_delay: Duration::from_secs(1), jitter: 0.25 };
+        let mut delays = Vec::new();
+        cached.refresh_with_policy(policy, async |delay| delays.push(delay)).await.unwrap();
+        assert_eq!(*cached.get().unwrap(), 200, "Expect value from the successful attempt to be cached");
+        assert_eq!(cached.refresh_count(), 3, "Expect {} to equals {}", cached.refresh_count(), 3);
+        assert_eq!(delays.len(), 2, "Expect a delay before each retry but got {:?}", delays);
+        assert!(delays[0] < delays[1], "Expect delay to grow but got {:?}", delays);
+        for (retry, delay) in delays.iter().enumerate() {
+            let backoff = policy.backoff(retry as u32);
+            assert!(*delay <= backoff && *delay >= backoff.mul_f64(0.75), "Expect {:?} to be within jitter bound of {:?}", delay, backoff);
+        }
+        let mut failing = Object::new(Duration::from_secs(1), 100, async || {Err::<u16, u8>(1)});
+        let capped = RetryPolicy { max_attempts: 4, base: Duration::from_millis(100), max_delay: Duration::from_millis(150), jitter: 0.0 };
+        let mut delays = Vec::new();
+        assert_eq!(failing.refresh_with_policy(capped, async |delay| delays.push(delay)).await, Err(1), "Expect last error once attempts run out");
+        assert_eq!(delays, vec![Duration::from_millis(100), Duration::from_millis(150), Duration::from_millis(150)], "Expect delay to be capped at max delay");
+        assert_eq!(*failing.get().unwrap(), 100, "Expect cached value to be kept when every attempt fail");
+    }
+    #[test]
+    fn replace_newer_version() {
+        let mut cached = Object::new(Duration::from_secs(1), 100, async || {Ok::<u16, ()>(200)});
+        assert!(cached.replace_if_newer(500, 5), "Expect first pushed value to be stored");
+        assert!(!cached.replace_if_newer(300, 3), "Expect older version to be ignored");
+        assert_eq!(*cached.get().unwrap(), 500, "Expect {} to equals {}", *cached.get().unwrap(), 500);
+        assert_eq!(cached.version(), Some(5), "Expect {:?} to equals {:?}", cached.version(), Some(5));
+        assert!(cached.replace_if_newer(600, 6), "Expect newer version to replace");
+        assert_eq!(*cached.get().unwrap(), 600, "Expect {} to equals {}", *cached.get().unwrap(), 600);
+    }
+    #[tokio::test]
+    async fn refresh_with_context() {
+        struct Request {
+            user: &'static str
+        }
+        let mut cached = Object::new_with_context(Duration::ZERO, String::new(), async |req: &Request| {Ok::<String, ()>(format!("profile of {}", req.user))});
+        let profile = cached.get_or_refresh_with(&Request { user: "alice" }).await.unwrap().clone();
+        assert_eq!(profile, "profile of alice", "Expect context to determine refreshed value");
+        sleep(Duration::from_millis(1));
+        let profile = cached.get_or_refresh_with(&Request { user: "bob" }).await.unwrap().clone();
+        assert_eq!(profile, "profile of bob", "Expect context of the refreshing caller to be used");
+        assert_eq!(cached.refresh_count(), 2, "Expect {} to equals {}", cached.refresh_count(), 2);
+    }
+    #[test]
+    fn freshness_ratio_validate() {
+        let mut cached = Object::new(Duration::from_millis(200), 100, async || {Ok::<u16, ()>(200)});
+        let ratio = cached.freshness_ratio();
+        assert!(ratio > 0.95 && ratio <= 1.0, "Expect ratio right after refresh to be about 1.0 but got {}", ratio);
+        cached.expire_in(Duration::from_millis(100));
+        let ratio = cached.freshness_ratio();
+        assert!((ratio - 0.5).abs() < 0.05, "Expect ratio midway through ttl to be about 0.5 but got {}", ratio);
+        cached.force_expire_at(clock::now());
+        assert_eq!(cached.freshness_ratio(), 0.0, "Expect value past its forced expiry to have no freshness");
+        cached.set(200);
+        cached.expire_in(Duration::ZERO);
+        sleep(Duration::from_millis(1));
+        assert_eq!(cached.freshness_ratio(), 0.0, "Expect expired value to have no freshness");
+    }
+    #[test]
+    fn fast_path_hit() {
+        let polled = std::cell::Cell::new(0);
+        let cached = Object::new(Duration::from_millis(50), 100, async || {
+            polled.set(polled.get() + 1);
+            Ok::<u16, ()>(200)
+        });
+        for _ in 0..1000 {
+            assert_eq!(cached.get_fast(), Some(&100), "Expect hit to be served synchronously");
+        }
+        assert_eq!(polled.get(), 0, "Expect hit path to never poll refresh future");
+        let mut cached = Object::new(Duration::ZERO, 100, async || {Ok::<u16, ()>(200)});
+        sleep(Duration::from_millis(1));
+        assert_eq!(cached.get_fast(), None, "Expect miss once the value expired");
+        cached.invalidate();
+        assert_eq!(cached.get_fast(), None, "Expect miss when the value is invalidated");
+    }
+    #[cfg(feature = "bytes")]
+    #[tokio::test]
+    async fn bytes_value() {
+        let payload = bytes::Bytes::from(vec![7u8; 4096]);
+        let mut cached = Object::new(Duration::from_secs(1), payload.clone(), async || {Ok::<bytes::Bytes, ()>(bytes::Bytes::from_static(b"new"))});
+        let first = cached.get_bytes_clone().unwrap();
+        let second = cached.get_bytes_clone().unwrap();
+        assert_eq!(first.as_ptr(), payload.as_ptr(), "Expect clone to share the cached buffer");
+        assert_eq!(second.as_ptr(), first.as_ptr(), "Expect every clone to share the same buffer");
+        cached.refresh().await.unwrap();
+        assert_eq!(cached.get_bytes_clone().unwrap(), &b"new"[..], "Expect refreshed payload to be returned");
+        assert_eq!(first.len(), 4096, "Expect earlier clone to keep the previous payload");
+    }
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn blocking_refresh() {
+        let caller = std::thread::current().id();
+        let mut cached = Object::new_blocking(Duration::from_secs(1), caller, || {
+            sleep(Duration::from_millis(10));
+            Ok::<std::thread::ThreadId, ()>(std::thread::current().id())
+        });
+        cached.refresh().await.unwrap();
+        assert_ne!(*cached.get().unwrap(), caller, "Expect blocking refresh to run on another thread");
+        assert_eq!(cached.refresh_count(), 1, "Expect {} to equals {}", cached.refresh_count(), 1);
+    }
+    #[tokio::test]
+    async fn borrowed_or_owned_value() {
+        fn total(value: &CacheValue<'_, Vec<u16>>) -> u16 {
+            value.iter().sum()
+        }
+        let mut cached = Object::new(Duration::from_secs(1), vec![1, 2], async || {Ok::<Vec<u16>, ()>(vec![3, 4])});
+        let borrowed = cached.get_or_refresh_value().await.unwrap();
+        assert!(matches!(borrowed, CacheValue::Borrowed(_)), "Expect {:?} to be borrowed", borrowed);
+        assert_eq!(total(&borrowed), 3, "Expect {} to equals {}", total(&borrowed), 3);
+        let shared = SharedCache::new(Duration::from_secs(1), vec![5, 6], || async {Ok::<Vec<u16>, ()>(vec![7, 8])});
+        let owned = shared.get_or_refresh_value().await.unwrap();
+        assert!(matches!(owned, CacheValue::Owned(_)), "Expect {:?} to be owned", owned);
+        assert_eq!(total(&owned), 11, "Expect {} to equals {}", total(&owned), 11);
+        drop(shared);
+        assert_eq!(*owned.into_arc(), vec![5, 6], "Expect owned value to outlive the cache");
+    }
+    #[tokio::test]
+    async fn diff_refresh() {
+        let mut next = 1u16;
+        let mut cached = Object::new(Duration::from_secs(1), 0, async || {next += 1; Ok::<u16, ()>(next)});
+        let before = *cached.get().unwrap();
+        let (previous, current) = cached.refresh_diff().await.unwrap();
+        assert_eq!(previous, Some(before), "Expect {:?} to equals {:?}", previous, Some(before));
+        assert_eq!(*current, 2, "Expect {} to equals {}", current, 2);
+        let (previous, current) = cached.refresh_diff().await.unwrap();
+        assert_eq!((previous, *current), (Some(2), 3), "Expect previous refresh to be the old value");
+    }
+    #[cfg(feature = "stream")]
+    #[tokio::test(start_paused = true)]
+    async fn refresh_stream() {
+        use futures_core::Stream;
+
+        let mut calls = 0u16;
+        let mut stream = Object::new(Duration::from_secs(1), 0, async move || {
+            calls += 1;
+            if calls == 2 { Err(()) } else { Ok::<u16, ()>(calls) }
+        }).into_stream(Duration::from_secs(10));
+        let start = tokio::time::Instant::now();
+        let mut items = Vec::new();
+        for _ in 0..4 {
+            items.push(std::future::poll_fn(|cx| Pin::new(&mut stream).poll_next(cx)).await.unwrap());
+        }
+        assert_eq!(items, vec![Ok(1), Err(()), Ok(3), Ok(4)], "Expect refreshed value on every tick and error to not end the stream");
+        assert_eq!(start.elapsed(), Duration::from_secs(30), "Expect one refresh per interval tick");
+    }
+    #[tokio::test]
+    async fn rate_limited_refresh() {
+        let mut calls = 0u16;
+        let mut cached = Object::new(Duration::ZERO, 0, async || {calls += 1; Ok::<u16, ()>(calls)})
+            .with_min_refresh_interval(Duration::from_millis(50), RateLimitAction::ServeStale);
+        cached.refresh().await.unwrap();
+        for _ in 0..10 {
+            cached.refresh().await.unwrap();
+            cached.invalidate();
+            assert_eq!(*cached.get_or_refresh().await.unwrap(), 1, "Expect stale value while rate limited");
+        }
+        assert_eq!(cached.refresh_count(), 1, "Expect a single backend call within min interval");
+        sleep(Duration::from_millis(60));
+        assert_eq!(*cached.get_or_refresh().await.unwrap(), 2, "Expect refresh once min interval elapsed");
+    }
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn rate_limited_wait() {
+        let mut calls = Vec::new();
+        let mut cached = Object::new(Duration::ZERO, (), async || {calls.push(std::time::Instant::now()); Ok::<(), ()>(())})
+            .with_min_refresh_interval(Duration::from_millis(20), RateLimitAction::Wait);
+        for _ in 0..3 {
+            cached.refresh().await.unwrap();
+        }
+        for spacing in calls.windows(2).map(|pair| pair[1] - pair[0]) {
+            assert!(spacing >= Duration::from_millis(19), "Expect backend calls to be spaced by min interval but got {:?}", spacing);
+        }
+        assert_eq!(calls.len(), 3, "Expect every refresh to eventually call refresh_fn");
+    }
+    #[test]
+    fn remaining_or_zero() {
+        let mut cached = Object::new(Duration::from_secs(60), 100, async || {Ok::<u16, ()>(200)});
+        let remaining = cached.ttl_remaining_or_zero();
+        assert!(remaining > Duration::from_secs(59) && remaining <= Duration::from_secs(60), "Expect about full ttl but got {:?}", remaining);
+        cached.expire_in(Duration::ZERO);
+        sleep(Duration::from_millis(1));
+        assert_eq!(cached.ttl_remaining_or_zero(), Duration::ZERO, "Expect {:?} to equals {:?}", cached.ttl_remaining_or_zero(), Duration::ZERO);
+    }
+    #[tokio::test]
+    async fn reject_invalid_refresh() {
+        let mut next = 0i16;
+        let mut cached = Object::new(Duration::from_secs(1), 100, async || {next -= 1; Ok::<i16, ()>(next)});
+        for _ in 0..3 {
+            assert_eq!(cached.refresh_validated(async |v: &i16| Ok(*v >= 0)).await, Ok(false), "Expect negative value to be rejected");
+        }
+        assert_eq!(*cached.get().unwrap(), 100, "Expect previous value to be retained");
+        assert_eq!(cached.stats(), CacheStats { refreshes: 3, rejected_refreshes: 3, ..CacheStats::default() }, "Expect every rejection to be counted");
+    }
+    #[tokio::test]
+    async fn fallback_on_failure() {
+        let mut cached = Object::new(Duration::ZERO, 100, async || {Err::<u16, &str>("down")});
+        sleep(Duration::from_millis(1));
+        assert_eq!(*cached.get_or_refresh_or(0).await, 0, "Expect fallback to be returned when refresh fail");
+        cached.set_ttl(Duration::from_secs(1));
+        assert_eq!(*cached.get().unwrap(), 0, "Expect fallback to be stored");
+        assert_eq!(cached.stats().errors, 1, "Expect {} to equals {}", cached.stats().errors, 1);
+    }
+    #[tokio::test]
+    async fn fallback_after_loaded() {
+        let mut up = true;
+        let mut cached = Object::new(Duration::ZERO, 100, async move || {
+            let result = if up { Ok::<u16, &str>(200) } else { Err("down") };
+            up = false;
+            result
+        });
+        sleep(Duration::from_millis(1));
+        assert_eq!(*cached.get_or_refresh_or(0).await, 200, "Expect refreshed value when refresh success");
+        sleep(Duration::from_millis(5));
+        assert_eq!(*cached.get_or_refresh_or(0).await, 200, "Expect previously loaded value instead of fallback");
+        assert!(cached.age_exceeds(Duration::from_millis(5)), "Expect last update time to be kept when refresh fail");
+        assert_eq!(cached.stats().errors, 1, "Expect the failed refresh to be counted");
+    }
+    #[test]
+    fn fallible_mapping() {
+        let mut cached = Object::new(Duration::from_secs(1), "12".to_string(), async || {Ok::<String, ()>("x".to_string())});
+        assert_eq!(cached.try_get_mapped(|v| v.parse::<u8>()).unwrap(), Ok(12), "Expect mapped value when valid");
+        cached.set("abc".to_string());
+        assert!(matches!(cached.try_get_mapped(|v| v.parse::<u8>()), Ok(Err(_))), "Expect mapping error inside a valid cache");
+        cached.invalidate();
+        assert!(cached.try_get_mapped(|v| v.parse::<u8>()).is_err(), "Expect expiry to be reported separately from mapping error");
+    }
+    #[tokio::test]
+    async fn prometheus_export() {
+        let mut calls = 0u8;
+        let mut cached = Object::new(Duration::from_secs(1), 100, async || {
+            calls += 1;
+            if calls == 1 { Err::<u16, ()>(()) } else { Ok(200) }
+        });
+        cached.invalidate();
+        assert!(cached.get_or_refresh().await.is_err(), "Expect first refresh to fail");
+        cached.get_or_refresh().await.unwrap();
+        cached.get_or_refresh().await.unwrap();
+        let stats = cached.stats();
+        assert_eq!(stats, CacheStats { hits: 1, misses: 2, refreshes: 2, errors: 1, rejected_refreshes: 0 }, "Expect {:?} to be counted", stats);
+        let text = stats.to_prometheus("user_cache");
+        for line in ["# TYPE user_cache_hits_total counter", "user_cache_hits_total 1", "user_cache_misses_total 2", "user_cache_refreshes_total 2", "user_cache_errors_total 1"] {
+            assert!(text.lines().any(|l| l == line), "Expect line {:?} in {}", line, text);
+        }
+    }
+    #[tokio::test]
+    async fn partial_refresh() {
+        let mut cached = Object::new(Duration::from_secs(60), vec![1, 2, 3], async || {Err::<Vec<u8>, _>(("degraded", Some(vec![1])))});
+        assert_eq!(cached.refresh_or_partial(Duration::from_secs(5)).await, Err("degraded"), "Expect error to be surfaced");
+        assert_eq!(*cached.get().unwrap(), vec![1], "Expect partial value to be cached");
+        let remaining = cached.time_remain();
+        assert!(remaining <= Duration::from_secs(5) && remaining > Duration::from_secs(4), "Expect reduced ttl but got {:?}", remaining);
+        assert_eq!(cached.ttl(), Duration::from_secs(60), "Expect configured ttl to be unchanged");
+    }
+    #[tokio::test]
+    async fn max_lifetime_from_creation() {
+        let mut cached = Object::new(Duration::from_secs(60), 100, async || {Ok::<u16, ()>(200)}).with_max_lifetime(Duration::from_millis(30));
+        sleep(Duration::from_millis(20));
+        cached.refresh().await.unwrap();
+        assert!(cached.get().is_ok(), "Expect value to be valid within its lifetime");
+        assert!(cached.time_remain() <= Duration::from_millis(10), "Expect time remain to be capped by lifetime but got {:?}", cached.time_remain());
+        sleep(Duration::from_millis(15));
+        assert!(cached.get().is_err(), "Expect value to expire even if recently refreshed");
+        cached.refresh().await.unwrap();
+        assert!(cached.get().is_ok(), "Expect refresh after lifetime to start a new lifetime");
+    }
+    #[tokio::test]
+    async fn run_refresh_fn_only() {
+        async fn load() -> Result<u16, ()> {
+            Ok(200)
+        }
+        let cached = Object::new(Duration::from_secs(60), 100, load);
+        assert_eq!(cached.run_refresh_fn().await, Ok(200), "Expect refresh_fn result to be returned");
+        assert_eq!(*cached.get().unwrap(), 100, "Expect cached value to be unchanged");
+        assert_eq!(cached.refresh_count(), 0, "Expect {} to equals {}", cached.refresh_count(), 0);
+    }
+    #[tokio::test]
+    async fn lazy_first_load() {
+        let mut cached = Object::new_lazy(Duration::from_secs(60), async || {Ok::<String, ()>("loaded".to_string())});
+        assert!(cached.get().is_err(), "Expect no value before first load");
+        assert!(matches!(cached.try_get(), Err(CacheError::NotLoaded)), "Expect NotLoaded before first load");
+        assert_eq!(cached.state(), CacheState::NeverLoaded, "Expect {:?} to equals {:?}", cached.state(), CacheState::NeverLoaded);
+        assert_eq!(cached.get_or_refresh().await.unwrap(), "loaded", "Expect first get_or_refresh to load the value");
+        assert_eq!(cached.last_refresh_reason(), Some(RefreshReason::NotLoaded), "Expect {:?} to equals {:?}", cached.last_refresh_reason(), Some(RefreshReason::NotLoaded));
+        assert_eq!(cached.try_get().ok().map(String::as_str), Some("loaded"), "Expect loaded value to be returned");
+        cached.expire_in(Duration::ZERO);
+        assert!(matches!(cached.try_get(), Err(CacheError::Expired)), "Expect Expired once the loaded value expire");
+    }
+    #[tokio::test]
+    async fn unified_cache_error() {
+        async fn read(cached: &LocalCache<u16, impl AsyncFnMut() -> Result<u16, &'static str>, &'static str>) -> Result<u16, CacheError<&'static str>> {
+            if let Ok(value) = cached.get_copy() {
+                return Ok(value)
+            }
+            cached.try_refresh_exclusive().await?;
+            Ok(cached.get_copy()?)
+        }
+        let cached = LocalCache::new(Duration::ZERO, 100, async || {Err::<u16, _>("down")});
+        sleep(Duration::from_millis(1));
+        let description = match read(&cached).await {
+            Ok(_) => "value",
+            Err(CacheError::Refresh(e)) => e,
+            Err(CacheError::Expired) => "expired",
+            Err(_) => "other"
+        };
+        assert_eq!(description, "down", "Expect refresh error to be converted but got {}", description);
+        let expired: CacheError = TimeoutError.into();
+        assert!(matches!(expired, CacheError::Expired), "Expect {:?} to be Expired", expired);
+    }
+    #[test]
+    fn initial_age_offset() {
+        let cached = Object::new(Duration::from_secs(60), 100, async || {Ok::<u16, ()>(200)}).with_initial_age(Duration::from_secs(20));
+        let remaining = cached.time_remain();
+        assert!(remaining <= Duration::from_secs(40), "Expect time remain to be shortened by initial age but got {:?}", remaining);
+        assert!(cached.get().is_ok(), "Expect value to be valid");
+    }
+    #[tokio::test]
+    async fn one_off_refresh_fn() {
+        let mut cached = Object::new(Duration::from_secs(60), 100, async || {Ok::<u16, ()>(200)});
+        cached.refresh_with(async || {Ok(300)}).await.unwrap();
+        assert_eq!(*cached.get().unwrap(), 300, "Expect {} to equals {}", *cached.get().unwrap(), 300);
+        cached.refresh().await.unwrap();
+        assert_eq!(*cached.get().unwrap(), 200, "Expect stored refresh_fn to be unchanged");
+    }
+    #[tokio::test]
+    async fn refresh_latency() {
+        let mut cached = Object::new(Duration::from_millis(50), 100, async || {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            Ok::<u16, ()>(200)
+        });
+        let (value, latency) = cached.get_or_refresh_timed().await.unwrap();
+        assert_eq!((*value, latency), (100, None), "Expect no latency on a cache hit");
+        sleep(Duration::from_millis(60));
+        let (value, latency) = cached.get_or_refresh_timed().await.unwrap();
+        assert_eq!(*value, 200, "Expect {} to equals {}", *value, 200);
+        assert!(latency.is_some_and(|latency| latency >= Duration::from_millis(10)), "Expect refresh latency but got {:?}", latency);
+    }
+    #[tokio::test]
+    async fn soft_and_hard_expiry() {
+        let mut responses = vec![Err("down"), Ok(200)];
+        let mut cached = Object::new_tiered(Duration::from_millis(20), Duration::from_millis(60), 100, async || {responses.remove(0)});
+        sleep(Duration::from_millis(30));
+        assert!(cached.get().is_ok(), "Expect value to be served between soft and hard ttl");
+        assert!(matches!(cached.state(), CacheState::Stale { remaining, .. } if remaining <= Duration::from_millis(30)), "Expect stale state but got {:?}", cached.state());
+        assert_eq!(*cached.get_or_refresh().await.unwrap(), 100, "Expect stale value to be served before hard ttl");
+        assert_eq!(cached.get_fast(), Some(&100), "Expect fast path to agree with get between soft and hard ttl");
+        let (value, latency) = cached.get_or_refresh_timed().await.unwrap();
+        assert_eq!((*value, latency), (100, None), "Expect timed lookup to serve the stale value without refresh");
+        assert_eq!(*cached.get_or_refresh_or(0).await, 100, "Expect fallback lookup to serve the stale value");
+        assert_eq!(cached.refresh_count(), 0, "Expect no blocking refresh before hard ttl");
+        sleep(Duration::from_millis(40));
+        assert!(cached.get().is_err(), "Expect value to be expired after hard ttl");
+        assert_eq!(cached.get_or_refresh().await, Err("down"), "Expect refresh error after hard ttl");
+        assert_eq!(*cached.get_or_refresh().await.unwrap(), 200, "Expect value to be refreshed once backend recover");
+    }
+    #[cfg(feature = "tokio")]
+    #[test]
+    fn blocking_refresh_with_handle() {
+        fn read(cached: &mut Object<u16, impl AsyncFnMut() -> Result<u16, ()>>, handle: &tokio::runtime::Handle) -> u16 {
+            *cached.get_or_refresh_blocking(handle).unwrap()
+        }
+        let runtime = tokio::runtime::Builder::new_multi_thread().worker_threads(1).enable_time().build().unwrap();
+        let mut cached = Object::new(Duration::ZERO, 100, async || {
+            tokio::time::sleep(Duration::from_millis(1)).await;
+            Ok::<u16, ()>(200)
+        });
+        sleep(Duration::from_millis(1));
+        assert_eq!(read(&mut cached, runtime.handle()), 200, "Expect expired value to be refreshed on the runtime");
+    }
+    #[tokio::test]
+    async fn loaded_flag() {
+        let mut cached = Object::new_lazy(Duration::from_secs(60), async || {Ok::<u16, ()>(200)});
+        assert!(!cached.is_loaded(), "Expect lazy cache to have no value");
+        cached.get_or_refresh().await.unwrap();
+        assert!(cached.is_loaded(), "Expect value to be loaded after first get_or_refresh");
+        assert!(Object::new(Duration::ZERO, 100, async || {Ok::<u16, ()>(200)}).is_loaded(), "Expect initial value to count as loaded");
+    }
+    #[tokio::test]
+    async fn scale_ttl_by_factor() {
+        let mut cached = Object::new(Duration::from_secs(10), 100, async || {Ok::<u16, ()>(200)}).with_ttl_bounds(Duration::from_secs(1), Duration::from_secs(30));
+        cached.refresh().await.unwrap();
+        cached.scale_ttl(2.0);
+        let remaining = cached.time_remain();
+        assert!(remaining > Duration::from_secs(19) && remaining <= Duration::from_secs(20), "Expect remaining window to double but got {:?}", remaining);
+        cached.scale_ttl(2.0);
+        assert_eq!(cached.ttl(), Duration::from_secs(30), "Expect ttl to be clamped to max bound");
+        cached.scale_ttl(0.0);
+        assert_eq!(cached.ttl(), Duration::from_secs(1), "Expect ttl to be clamped to min bound");
+    }
+    #[tokio::test]
+    async fn refresh_metadata() {
+        let mut generation = 0u64;
+        let mut cached = Object::new_with_metadata(Duration::from_secs(60), 100, async || {
+            generation += 1;
+            Ok::<(u16, u64), ()>((200, generation))
+        });
+        assert_eq!(cached.metadata(), None, "Expect no metadata before first refresh");
+        cached.refresh().await.unwrap();
+        cached.refresh().await.unwrap();
+        assert_eq!(cached.metadata(), Some(&2), "Expect {:?} to equals {:?}", cached.metadata(), Some(&2));
+        assert_eq!(*cached.get_value().unwrap(), 200, "Expect {} to equals {}", *cached.get_value().unwrap(), 200);
+        assert_eq!(**cached.get().unwrap(), 200, "Expect cached value to dereference to the value without metadata");
+    }
+    #[tokio::test]
+    async fn plain_fn_refresh() {
+        fn load() -> impl Future<Output = Result<u16, ()>> {
+            let v = 200;
+            async move { Ok(v) }
+        }
+        let mut cached = Object::new(Duration::from_secs(1), 100, load);
+        assert_eq!(*cached.get().unwrap(), 100, "Expect initial value to be returned");
+        cached.refresh().await.unwrap();
+        assert_eq!(*cached.get().unwrap(), 200, "Expect value from plain fn to be cached");
+    }
+    #[test]
+    fn age_threshold() {
+        let cached = Object::new(Duration::from_secs(60), 100, async || {Ok::<u16, ()>(200)}).with_initial_age(Duration::from_secs(10));
+        assert!(cached.age_exceeds(Duration::from_secs(5)), "Expect value aged past threshold to exceed it");
+        assert!(!cached.age_exceeds(Duration::from_secs(30)), "Expect value under threshold to not exceed it");
+        assert!(cached.get().is_ok(), "Expect threshold to be independent of ttl");
+    }
+    #[tokio::test]
+    async fn expired_placeholder() {
+        let mut cached = Object::with_initial_and_lazy_ttl(Duration::from_secs(60), 100, async || {Ok::<u16, ()>(200)});
+        assert!(cached.get().is_err(), "Expect placeholder to be expired");
+        assert_eq!(cached.peek(), Some(&100), "Expect placeholder to be readable by peek");
+        assert_eq!(*cached.get_or_refresh().await.unwrap(), 200, "Expect first get_or_refresh to load the value");
+        assert_eq!(*cached.get().unwrap(), 200, "Expect refreshed value to use the configured ttl");
+        let lazy = Object::new_lazy(Duration::from_secs(60), async || {Ok::<u16, ()>(200)});
+        assert_eq!(lazy.peek(), None, "Expect no value to peek before first load");
+    }
+    #[test]
+    fn debug_age_unit() {
+        let cached = Object::new(Duration::from_secs(60), 100, async || {Ok::<u16, ()>(200)}).with_initial_age(Duration::from_secs(5));
+        let text = format!("{:?}", cached);
+        assert!(text.contains("elapsed: 5.0 s"), "Expect age in seconds but got {}", text);
+        let text = format!("{:?}", cached.with_initial_age(Duration::from_secs(150)));
+        assert!(text.contains("elapsed: 2.5 min"), "Expect age in minutes but got {}", text);
+        let text = format!("{:?}", Object::new(Duration::from_secs(60), 100, async || {Ok::<u16, ()>(200)}).with_initial_age(Duration::from_millis(250)));
+        assert!(text.contains("elapsed: 25") && text.contains(" ms,"), "Expect age in milliseconds but got {}", text);
+    }
+    #[tokio::test]
+    async fn pending_refresh() {
+        let mut responses = vec![Poll::Pending, Poll::Pending, Poll::Ready(200)];
+        let mut cached = Object::new_lazy_polling(Duration::from_secs(60), async || {Ok::<Poll<u16>, ()>(responses.remove(0))});
+        assert_eq!(cached.get_or_refresh_polling(Duration::from_millis(20)).await, Ok(Poll::Pending), "Expect pending on first refresh");
+        assert_eq!(cached.get_or_refresh_polling(Duration::from_millis(20)).await, Ok(Poll::Pending), "Expect pending within retry delay");
+        assert_eq!(cached.refresh_count(), 1, "Expect no refresh within retry delay");
+        assert!(!cached.is_loaded(), "Expect cache to stay unloaded while pending");
+        sleep(Duration::from_millis(25));
+        assert_eq!(cached.get_or_refresh_polling(Duration::ZERO).await, Ok(Poll::Pending), "Expect pending on second refresh");
+        assert_eq!(cached.get_or_refresh_polling(Duration::ZERO).await, Ok(Poll::Ready(&200)), "Expect value to load once ready");
+        assert_eq!(cached.stats().errors, 0, "Expect pending to not count as error");
+    }
+    #[tokio::test]
+    async fn await_value() {
+        let mut cached = Object::new(Duration::ZERO, 100, async || {Ok::<u16, ()>(200)});
+        sleep(Duration::from_millis(1));
+        assert_eq!(*cached.value().await.unwrap(), 200, "Expect awaited value to be refreshed");
+        cached.set_ttl(Duration::from_secs(60));
+        assert_eq!(*cached.value().await.unwrap(), 200, "Expect awaited value to be cached");
+        assert_eq!(cached.refresh_count(), 1, "Expect {} to equals {}", cached.refresh_count(), 1);
+    }
+    #[test]
+    fn owned_snapshot() {
+        let mut cached = Object::new(Duration::from_secs(60), vec![1u8, 2], async || {Ok::<Vec<u8>, ()>(vec![3])});
+        cached.invalidate();
+        let mut snapshot = cached.clone_value();
+        snapshot.push(3);
+        assert_eq!(snapshot, vec![1, 2, 3], "Expect clone to be mutable even though the value is expired");
+        assert_eq!(cached.peek(), Some(&vec![1, 2]), "Expect cached value to be unaffected by the clone");
+        assert_eq!(cached.refresh_count(), 0, "Expect no refresh");
+    }
+    #[test]
+    fn touch_with_new_ttl() {
+        let mut cached = Object::new(Duration::from_secs(60), 100, async || {Ok::<u16, ()>(200)}).with_initial_age(Duration::from_secs(30));
+        cached.set_ttl_and_touch(Duration::from_secs(10));
+        assert!(cached.get().is_ok(), "Expect old age to not apply to the new ttl");
+        let remaining = cached.time_remain();
+        assert!(remaining > Duration::from_secs(9) && remaining <= Duration::from_secs(10), "Expect full new window but got {:?}", remaining);
+        assert_eq!(cached.refresh_count(), 0, "Expect no refresh");
+    }
+    #[tokio::test]
+    async fn consistent_observation() {
+        let cached = tokio::sync::RwLock::new(Object::new(Duration::from_millis(20), 100, async || {Ok::<u16, ()>(200)}));
+        let fresh = cached.read().await.observe();
+        assert!(!fresh.is_expired && fresh.is_loaded, "Expect fresh value but got {:?}", fresh);
+        assert!(fresh.age + fresh.remaining_ttl <= Duration::from_millis(20), "Expect age and remaining ttl to add up to ttl but got {:?}", fresh);
+        sleep(Duration::from_millis(30));
+        let expired = cached.read().await.observe();
+        assert_eq!(expired.is_expired, expired.remaining_ttl.is_zero(), "Expect expiry to match remaining ttl but got {:?}", expired);
+        assert!(expired.is_expired && expired.age >= Duration::from_millis(30), "Expect expired value but got {:?}", expired);
+        cached.write().await.get_or_refresh().await.unwrap();
+        let refreshed = cached.read().await.observe();
+        assert_eq!(refreshed.is_expired, refreshed.remaining_ttl.is_zero(), "Expect expiry to match remaining ttl but got {:?}", refreshed);
+        assert_eq!(refreshed.stats, CacheStats { misses: 1, refreshes: 1, ..CacheStats::default() }, "Expect {:?} to be counted", refreshed.stats);
+    }
+    #[tokio::test]
+    async fn fallback_source() {
+        let primary: RefreshSource<u16, &str> = Box::new(|| Box::pin(async {Err("primary down")}));
+        let secondary: RefreshSource<u16, &str> = Box::new(|| Box::pin(async {Ok(200)}));
+        let tertiary: RefreshSource<u16, &str> = Box::new(|| Box::pin(async {Ok(300)}));
+        let mut cached = Object::with_fallback_chain(Duration::from_secs(60), 100, vec![primary, secondary, tertiary]);
+        cached.refresh().await.unwrap();
+        assert_eq!(*cached.get_value().unwrap(), 200, "Expect value from the secondary source");
+        assert_eq!(cached.refresh_source(), Some(1), "Expect {:?} to equals {:?}", cached.refresh_source(), Some(1));
+        assert_eq!(**cached.get().unwrap(), 200, "Expect cached value to hold only the value");
+        let failing: Vec<RefreshSource<u16, &str>> = vec![Box::new(|| Box::pin(async {Err("primary down")})), Box::new(|| Box::pin(async {Err("secondary down")}))];
+        let mut cached = Object::with_fallback_chain(Duration::from_secs(60), 100, failing);
+        assert_eq!(cached.refresh().await, Err("secondary down"), "Expect error of the last source when every source fail");
+        assert_eq!(cached.refresh_source(), None, "Expect no source when nothing was loaded");
+    }
+    #[tokio::test]
+    async fn scheduled_expiry() {
+        let mut cached = Object::new(Duration::from_secs(60), 100, async || {Ok::<u16, ()>(200)}).with_schedule(|updated| updated + Duration::from_millis(30));
+        assert!(cached.time_remain() <= Duration::from_millis(30), "Expect expiry at the scheduled instant but got {:?}", cached.time_remain());
+        sleep(Duration::from_millis(10));
+        assert!(cached.get().is_ok(), "Expect value to be valid before the scheduled instant");
+        sleep(Duration::from_millis(30));
+        assert!(cached.get().is_err(), "Expect value to be expired at the scheduled instant");
+        assert_eq!(*cached.get_or_refresh().await.unwrap(), 200, "Expect expired value to be refreshed");
+        assert!(cached.time_remain() <= Duration::from_millis(30), "Expect next expiry to be scheduled from the refresh");
+        assert!(cached.get().is_ok(), "Expect refreshed value to be valid until the next scheduled instant");
+    }
+    #[tokio::test]
+    async fn sliding_expiry() {
+        let mut cached = Object::new(Duration::from_millis(40), 100, async || {Ok::<u16, ()>(200)});
+        for _ in 0..3 {
+            let value = cached.get_or_refresh_sliding().await.unwrap();
+            assert_eq!(*value, 100, "Expect used value to not be refreshed");
+            drop(value);
+            sleep(Duration::from_millis(25));
+        }
+        assert!(cached.get().is_ok(), "Expect dropping the guard to extend the ttl");
+        assert_eq!(cached.refresh_count(), 0, "Expect {} to equals {}", cached.refresh_count(), 0);
+        sleep(Duration::from_millis(20));
+        assert!(cached.get().is_err(), "Expect value to expire once unused for ttl");
+    }
+    #[tokio::test]
+    async fn parts_of_loaded_value() {
+        let cached = Object::new_lazy(Duration::from_secs(60), async || {Ok::<u16, ()>(200)});
+        assert!(matches!(cached.try_into_parts(), Err(NotLoaded)), "Expect NotLoaded before the first load");
+        let mut cached = Object::new_lazy(Duration::from_secs(60), async || {Ok::<u16, ()>(200)});
+        cached.get_or_refresh().await.unwrap();
+        let (ttl, _, obj, mut refresh_fn) = cached.try_into_parts().unwrap();
+        assert_eq!((ttl, obj), (Duration::from_secs(60), 200), "Expect ttl and loaded value to be returned");
+        assert_eq!(refresh_fn().await, Ok(200), "Expect refresh_fn to be returned");
+    }
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn hedged_refresh() {
+        let calls = std::cell::Cell::new(0u16);
+        let load = async || {
+            calls.set(calls.get() + 1);
+            let call = calls.get();
+            if call == 1 {
+                tokio::time::sleep(Duration::from_millis(500)).await;
+            }
+            Ok::<u16, ()>(call)
+        };
+        let mut cached = Object::new(Duration::from_secs(60), 0, load);
+        let start = std::time::Instant::now();
+        cached.refresh_hedged(Duration::from_millis(20)).await.unwrap();
+        assert!(start.elapsed() < Duration::from_millis(200), "Expect slow first call to be hedged but it took {:?}", start.elapsed());
+        assert_eq!(*cached.get().unwrap(), 2, "Expect result of the faster hedge call to be used");
+        assert_eq!(cached.refresh_count(), 2, "Expect {} to equals {}", cached.refresh_count(), 2);
+        cached.refresh_hedged(Duration::from_millis(20)).await.unwrap();
+        assert_eq!((*cached.get().unwrap(), cached.refresh_count()), (3, 3), "Expect fast call to not be hedged");
+    }
+    #[tokio::test]
+    async fn async_transform() {
+        let mut cached = Object::new(Duration::from_secs(60), "plain".to_string(), async || {Ok::<String, &str>("cipher".to_string())})
+            .map_async(async |v: String| {
+                tokio::task::yield_now().await;
+                if v.is_empty() { Err("empty") } else { Ok(format!("decrypted {}", v)) }
+            });
+        assert_eq!(cached.get().unwrap(), "plain", "Expect current value to be kept");
+        cached.refresh().await.unwrap();
+        assert_eq!(cached.get().unwrap(), "decrypted cipher", "Expect refreshed value to be transformed before it is stored");
+        cached.refresh().await.unwrap();
+        assert_eq!(cached.get().unwrap(), "decrypted cipher", "Expect transform to apply on every refresh");
+    }
+    #[tokio::test]
+    async fn post_transform() {
+        let mut count = 0;
+        let mut cached = Object::new(Duration::from_secs(60), "plain".to_string(), async move || {
+            count += 1;
+            Ok::<String, &str>(format!("cipher {}", count))
+        });
+        cached.set_post_transform(async |v: String| {
+            tokio::task::yield_now().await;
+            if v.ends_with('3') { Err("bad key") } else { Ok(format!("decrypted {}", v)) }
+        });
+        assert_eq!(cached.get().unwrap(), "plain", "Expect current value to be kept");
+        cached.refresh().await.unwrap();
+        assert_eq!(cached.get().unwrap(), "decrypted cipher 1", "Expect refreshed value to be transformed before it is stored");
+        cached.refresh_with(async || Ok("full 2".to_string())).await.unwrap();
+        assert_eq!(cached.get().unwrap(), "decrypted full 2", "Expect transform to apply on every refresh path");
+        cached.refresh().await.unwrap();
+        assert_eq!(cached.get().unwrap(), "decrypted cipher 2", "Expect transform to apply on every refresh");
+        assert_eq!(cached.refresh().await, Err("bad key"), "Expect transform error to fail the refresh");
+        assert_eq!(cached.get().unwrap(), "decrypted cipher 2", "Expect failed transform to keep the cached value");
+        assert_eq!(cached.stats().errors, 1, "Expect transform error to be counted");
+        let mut mapped = cached.map_async(async |v: String| Ok(v.to_uppercase()));
+        mapped.refresh().await.unwrap();
+        assert_eq!(mapped.get().unwrap(), "decrypted CIPHER 4", "Expect transform to be kept and applied after map_async");
+    }
+    #[test]
+    fn reroll_ttl_jitter() {
+        let mut cached = Object::new(Duration::from_secs(100), 100, async || {Ok::<u16, ()>(200)}).with_ttl_jitter(0.5);
+        let within_bounds = |remaining: Duration| remaining > Duration::from_secs(49) && remaining <= Duration::from_secs(100);
+        let first = cached.time_remain();
+        assert!(within_bounds(first), "Expect jittered remaining ttl within bounds but got {:?}", first);
+        let mut changed = false;
+        for _ in 0..10 {
+            cached.reroll_jitter();
+            let remaining = cached.time_remain();
+            assert!(within_bounds(remaining), "Expect rerolled remaining ttl within bounds but got {:?}", remaining);
+            changed |= remaining.abs_diff(first) > Duration::from_millis(100);
+        }
+        assert!(changed, "Expect reroll to change the remaining ttl");
+        assert_eq!(cached.ttl(), Duration::from_secs(100), "Expect configured ttl to be unchanged");
+        assert_eq!(cached.refresh_count(), 0, "Expect no refresh");
+    }
+    #[test]
+    fn jitter_without_overflow() {
+        let constant = Object::<u16, (), ()>::constant(100).with_ttl_jitter(0.0);
+        assert_eq!(constant.get().ok(), Some(&100), "Expect permanent value with zero jitter to be returned");
+        let nan = Object::new(Duration::from_secs(100), 100, async || {Ok::<u16, ()>(200)}).with_ttl_jitter(f64::NAN);
+        assert!(nan.time_remain() > Duration::from_secs(99), "Expect NaN jitter to be treated as zero but got {:?}", nan.time_remain());
+    }
+    #[tokio::test]
+    async fn success_and_error_age() {
+        let mut responses = vec![Ok(200), Err(())];
+        let mut cached = Object::new(Duration::from_secs(60), 100, async || {responses.remove(0)});
+        assert_eq!((cached.since_last_success(), cached.since_last_error()), (None, None), "Expect nothing recorded before any refresh");
+        cached.refresh().await.unwrap();
+        sleep(Duration::from_millis(20));
+        assert!(cached.refresh().await.is_err(), "Expect second refresh to fail");
+        let (success, error) = (cached.since_last_success().unwrap(), cached.since_last_error().unwrap());
+        assert!(success >= Duration::from_millis(20), "Expect time since success to cover the failing period but got {:?}", success);
+        assert!(error < success, "Expect last error {:?} to be more recent than last success {:?}", error, success);
+    }
+    #[tokio::test]
+    async fn refresh_older_than_threshold() {
+        let mut count = 0;
+        let mut cached = Object::new(Duration::from_secs(60), 100, async || {count += 1; Ok::<i32, ()>(count)});
+        assert!(!cached.refresh_if_older_than(Duration::from_millis(20)).await.unwrap(), "Expect fresh value to be kept");
+        sleep(Duration::from_millis(30));
+        assert_eq!(cached.get().ok(), Some(&100), "Expect value within ttl to be served");
+        assert!(cached.refresh_if_older_than(Duration::from_millis(20)).await.unwrap(), "Expect value older than threshold to be refreshed");
+        assert_eq!(cached.get().ok(), Some(&1));
+        assert!(!cached.refresh_if_older_than(Duration::from_millis(20)).await.unwrap(), "Expect refreshed value to be kept");
+    }
+    trait Shape: Send + Sync {
+        fn area(&self) -> u32;
+    }
+    struct Square(u32);
+    impl Shape for Square {
+        fn area(&self) -> u32 {self.0 * self.0}
+    }
+    struct Rectangle(u32, u32);
+    impl Shape for Rectangle {
+        fn area(&self) -> u32 {self.0 * self.1}
+    }
+    #[tokio::test]
+    async fn trait_object() {
+        let initial: Arc<dyn Shape> = Arc::new(Square(2));
+        let mut cached = Object::new_boxed(Duration::from_secs(60), initial, async || {Ok::<Box<dyn Shape>, ()>(Box::new(Rectangle(2, 3)))});
+        let before = cached.get_or_refresh_arc().await.unwrap();
+        cached.refresh().await.unwrap();
+        assert_eq!(cached.get().unwrap().area(), 6, "Expect refresh to switch to another implementation");
+        assert_eq!(before.area(), 4, "Expect previous handle to keep the previous implementation");
+    }
+    #[tokio::test]
+    async fn forced_expiry() {
+        let mut cached = Object::new(Duration::from_secs(60), 100, async || {Ok::<i32, ()>(200)});
+        cached.force_expire_at(clock::now() + Duration::from_millis(20));
+        assert!(cached.get().is_ok(), "Expect value to be valid before forced expiry");
+        assert!(cached.time_remain() <= Duration::from_millis(20), "Expect forced expiry to shorten remaining time");
+        sleep(Duration::from_millis(30));
+        assert!(cached.get().is_err(), "Expect value to be expired after forced expiry");
+        assert_eq!(cached.get_or_refresh().await, Ok(&200));
+        assert!(cached.time_remain() > Duration::from_secs(59), "Expect refreshed value to follow ttl again");
+        cached.force_expire_at(SystemTime::UNIX_EPOCH);
+        assert!(cached.get().is_err(), "Expect expiry in the past to expire immediately");
+    }
+    #[tokio::test]
+    async fn refresh_spacing() {
+        let mut refreshed_at = Vec::new();
+        let mut cached = Object::new(Duration::ZERO, 0, async || {refreshed_at.push(SystemTime::now()); Ok::<usize, ()>(refreshed_at.len())})
+            .with_refresh_at_most_every(Duration::from_millis(50));
+        let start = SystemTime::now();
+        while start.elapsed().unwrap() < Duration::from_millis(120) {
+            assert!(cached.get_or_refresh().await.is_ok(), "Expect existing value to be served between refreshes");
+            sleep(Duration::from_millis(5));
+        }
+        assert!(refreshed_at.len() <= 3, "Expect at most one refresh per 50ms but got {}", refreshed_at.len());
+        for pair in refreshed_at.windows(2) {
+            let spacing = pair[1].duration_since(pair[0]).unwrap();
+            assert!(spacing >= Duration::from_millis(50), "Expect refreshes to be at least 50ms apart but got {:?}", spacing);
+        }
+    }
+    #[tokio::test]
+    async fn constant_value() {
+        let mut cached: Object<_, _, ()> = Object::constant(100);
+        for _ in 0..3 {
+            assert_eq!(cached.get().ok(), Some(&100), "Expect constant value to stay valid");
+            sleep(Duration::from_millis(5));
+        }
+        cached.refresh().await.unwrap();
+        assert_eq!(cached.get().ok(), Some(&100), "Expect refresh to keep the same value");
+        assert!(cached.time_remain() > Duration::from_secs(100 * 365 * 24 * 3600), "Expect constant value to never expire");
+    }
+    #[tokio::test]
+    async fn refresh_duration() {
+        let mut responses = vec![Ok(200), Err(())];
+        let mut cached = Object::new(Duration::from_secs(60), 100, async || {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            responses.remove(0)
+        });
+        assert_eq!(cached.last_refresh_duration(), None, "Expect no duration before any refresh");
+        cached.refresh().await.unwrap();
+        let duration = cached.last_refresh_duration().unwrap();
+        assert!(duration >= Duration::from_millis(20), "Expect duration of successful refresh to cover the sleep but got {:?}", duration);
+        assert!(cached.refresh().await.is_err(), "Expect second refresh to fail");
+        let duration = cached.last_refresh_duration().unwrap();
+        assert!(duration >= Duration::from_millis(20), "Expect duration of failed refresh to cover the sleep but got {:?}", duration);
+    }
+    #[test]
+    fn swap_value() {
+        let mut cached = Object::new(Duration::from_millis(40), 100, async || {Ok::<i32, ()>(200)});
+        sleep(Duration::from_millis(30));
+        assert_eq!(cached.swap(300), 100, "Expect previous value to be returned");
+        assert_eq!(cached.get().ok(), Some(&300), "Expect swapped value to be cached");
+        sleep(Duration::from_millis(20));
+        assert!(cached.get().is_ok(), "Expect swap to reset the ttl");
+    }
+    #[tokio::test]
+    async fn transformed_refresh() {
+        let mut body = String::new();
+        let mut cached = Object::new_with_transform(Duration::ZERO, 0, async || {body.push('a'); Ok::<String, ()>(body.clone())}, |raw: String| raw.len());
+        assert_eq!(cached.get_or_refresh().await, Ok(&1), "Expect fetched string to be cached as its length");
+        assert_eq!(cached.get_or_refresh().await, Ok(&2), "Expect transform to be applied on every refresh");
+    }
+    #[tokio::test]
+    async fn rate_limit_without_value() {
+        let mut responses = vec![Err(()), Ok(200)];
+        let mut cached = Object::new_lazy(Duration::ZERO, async || {responses.remove(0)})
+            .with_min_refresh_interval(Duration::from_secs(60), RateLimitAction::ServeStale);
+        assert_eq!(cached.get_or_refresh().await, Err(()), "Expect first load to fail");
+        assert_eq!(cached.get_or_refresh().await, Ok(&200), "Expect load to not be skipped when there's no value to serve");
+        cached.refresh_with(async || Ok(300)).await.unwrap();
+        assert_eq!(cached.peek(), Some(&200), "Expect refresh_with to be rate limited");
+        assert_eq!(cached.refresh_count(), 2, "Expect {} to equals {}", cached.refresh_count(), 2);
+    }
 }
\ No newline at end of file
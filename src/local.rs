@@ -0,0 +1,113 @@
+//! A single threaded cached object that can be refreshed through a shared reference.
+use std::cell::{Ref, RefCell};
+use std::time::Duration;
+
+use crate::{Object, RefreshError, TimeoutError};
+
+/// Single threaded wrapper of [Object] which use [RefCell] so that [LocalCache::get_or_refresh()]
+/// only need `&self`. This is useful when the cache live inside a struct that is shared by reference.
+/// For a [Sync] version that can be shared between threads, use [crate::SharedCache].
+///
+/// # Panics
+/// The cached value is returned as [Ref] which borrow the inner [RefCell]. Any method that may refresh
+/// the value, e.g. [LocalCache::refresh()] or [LocalCache::get_or_refresh()], panic if a [Ref] returned
+/// earlier is still alive or if it is called again while another refresh is still pending.
+/// Drop the returned [Ref] before the next call that may refresh.
+/// [LocalCache::try_refresh_exclusive()] return [RefreshError::AlreadyRefreshing] instead of panicking.
+/// ```rust
+/// use std::time::Duration;
+/// use generic_cache::LocalCache;
+///
+/// # tokio_test::block_on(async {
+/// let cached = LocalCache::new(Duration::ZERO, 100, async || {Ok::<u16, ()>(200)});
+/// let shared = &cached;
+/// assert_eq!(*shared.get_or_refresh().await.unwrap(), 200);
+/// # })
+/// ```
+pub struct LocalCache<T, F, E = ()> where F: AsyncFnMut() -> Result<T, E> {
+    inner: RefCell<Object<T, F, E>>
+}
+impl<T, F, E> From<Object<T, F, E>> for LocalCache<T, F, E> where F: AsyncFnMut() -> Result<T, E> {
+    fn from(obj: Object<T, F, E>) -> Self {
+        LocalCache {
+            inner: RefCell::new(obj)
+        }
+    }
+}
+impl<T, F, E> LocalCache<T, F, E> where F: AsyncFnMut() -> Result<T, E> {
+    /// Create a new cache with default value specify in second argument. See [Object::new()].
+    pub fn new(ttl: Duration, obj: T, refresh_fn: F) -> LocalCache<T, F, E> {
+        Object::new(ttl, obj, refresh_fn).into()
+    }
+    /// Refresh cache immediately and update last update time if refresh success.
+    #[allow(clippy::await_holding_refcell_ref, reason = "the borrow panic is documented on LocalCache")]
+    pub async fn refresh(&self) -> Result<(), E> {
+        self.inner.borrow_mut().refresh().await
+    }
+    /// Same as [LocalCache::refresh()] but if another refresh is in progress, e.g. from another task on the
+    /// same thread, or a [Ref] returned earlier is still alive, [RefreshError::AlreadyRefreshing] is returned
+    /// instead of panicking so the same work isn't done twice.
+    #[allow(clippy::await_holding_refcell_ref, reason = "the borrow is exactly what guard overlapping refresh")]
+    pub async fn try_refresh_exclusive(&self) -> Result<(), RefreshError<E>> {
+        let mut obj = self.inner.try_borrow_mut().map_err(|_| RefreshError::AlreadyRefreshing)?;
+        obj.refresh().await.map_err(RefreshError::Refresh)
+    }
+    /// Read current cached value or return Error if cache is already expired.
+    pub fn get(&self) -> Result<Ref<'_, T>, TimeoutError> {
+        Ref::filter_map(self.inner.borrow(), |obj| obj.get().ok()).map_err(|_| TimeoutError {})
+    }
+    /// Hot path for [Copy] value. Return a copy of current cached value so there's no [Ref] to keep track of
+    /// and it never conflict with a later refresh. Return Error if cache is already expired.
+    pub fn get_copy(&self) -> Result<T, TimeoutError> where T: Copy {
+        self.inner.borrow().get_copy()
+    }
+    /// Read current cached value or refresh the value if it is already expired then
+    /// return the new value.
+    #[allow(clippy::await_holding_refcell_ref, reason = "the borrow panic is documented on LocalCache")]
+    pub async fn get_or_refresh(&self) -> Result<Ref<'_, T>, E> {
+        self.inner.borrow_mut().get_or_refresh().await?;
+        Ok(Ref::map(self.inner.borrow(), |obj| &obj.obj))
+    }
+    /// Consume this wrapper and return the wrapped [Object].
+    pub fn into_inner(self) -> Object<T, F, E> {
+        self.inner.into_inner()
+    }
+}
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn refresh_through_shared_reference() {
+        struct Service<'a, F> where F: AsyncFnMut() -> Result<u16, ()> {
+            cached: &'a LocalCache<u16, F>
+        }
+        let cached = LocalCache::new(Duration::ZERO, 100, async || {Ok::<u16, ()>(200)});
+        let first = Service { cached: &cached };
+        let second = Service { cached: &cached };
+        assert_eq!(*first.cached.get_or_refresh().await.unwrap(), 200, "Expect expired value to be refreshed through &self");
+        assert_eq!(*second.cached.get_or_refresh().await.unwrap(), 200, "Expect another holder to refresh through the same cache");
+        assert_eq!(cached.into_inner().refresh_count(), 2, "Expect every expired read to refresh");
+    }
+    #[tokio::test]
+    async fn overlapping_refresh() {
+        let cached = LocalCache::new(Duration::from_secs(1), 100, async || {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            Ok::<u16, ()>(200)
+        });
+        let (first, second) = tokio::join!(cached.try_refresh_exclusive(), cached.try_refresh_exclusive());
+        assert!(first.is_ok(), "Expect first refresh to succeed but got {:?}", first);
+        assert!(matches!(second, Err(RefreshError::AlreadyRefreshing)), "Expect overlapping refresh to be rejected but got {:?}", second);
+        assert_eq!(cached.into_inner().refresh_count(), 1, "Expect no double work");
+    }
+    #[tokio::test]
+    async fn copy_value() {
+        let cached = LocalCache::new(Duration::from_secs(1), (1u8, 2.5f32), async || {Ok::<(u8, f32), ()>((2, 5.0))});
+        let value = cached.get_copy().unwrap();
+        cached.refresh().await.unwrap();
+        assert_eq!(value, (1, 2.5), "Expect copied value to be kept after refresh");
+        assert_eq!(cached.get_copy().unwrap(), (2, 5.0), "Expect {:?} to equals {:?}", cached.get_copy(), (2, 5.0));
+    }
+}
@@ -0,0 +1,560 @@
+//! A keyed cache where every entry expire on its own.
+use std::collections::{BTreeMap, HashMap};
+use std::future::{poll_fn, Future};
+use std::hash::Hash;
+use std::task::Poll;
+use std::time::{Duration, SystemTime};
+
+use crate::clock;
+
+struct Entry<V> {
+    obj: V,
+    last_update: SystemTime,
+    last_used: u64,
+    weight: u64
+}
+impl<V> Entry<V> {
+    fn is_expired(&self, ttl: Duration) -> bool {
+        clock::elapsed(self.last_update) > ttl
+    }
+}
+
+/// Function that compute the weight of a value, see [CacheMap::with_weigher()].
+type Weigher<V> = Box<dyn Fn(&V) -> u64 + Send + Sync>;
+
+/// Generic keyed cache which cache each value for given period of time.
+/// The `refresh_fn` is a batch loader. It is an async function which receive every key that need to be
+/// refreshed at once, up to [CacheMap::with_max_batch_size()], and return the new value of those keys.
+/// This allow using a backend call that can load many keys in one request instead of one request per key.
+/// Key that is not part of the returned map is left untouched.
+///
+/// The cache is unbounded unless [CacheMap::with_weigher()] is used to bound the total weight of its values.
+/// Every method that may refresh take `&mut self`. To share a keyed cache between tasks with concurrent
+/// refresh of different keys, use [crate::SharedCacheMap] instead.
+/// ```rust
+/// use std::collections::HashMap;
+/// use std::time::Duration;
+/// use generic_cache::CacheMap;
+///
+/// # tokio_test::block_on(async {
+/// let mut cached = CacheMap::new(Duration::from_secs(1), async |keys: &[u16]| {
+///     Ok::<HashMap<u16, u16>, ()>(keys.iter().map(|k| (*k, k * 2)).collect())
+/// });
+/// let values = cached.get_or_refresh_batch(&[1, 2]).await.unwrap();
+/// assert_eq!(values, vec![Some(&2), Some(&4)]);
+/// # })
+/// ```
+pub struct CacheMap<K, V, F, E = ()> where F: AsyncFnMut(&[K]) -> Result<HashMap<K, V>, E> {
+    ttl: Duration,
+    entries: HashMap<K, Entry<V>>,
+    /// Key of every entry ordered by its last use so the least recently used one is evicted without a scan.
+    usage: BTreeMap<u64, K>,
+    refresh_fn: F,
+    tick: u64,
+    weigher: Option<Weigher<V>>,
+    max_weight: u64,
+    total_weight: u64,
+    max_batch_size: usize
+}
+impl<K, V, F, E> CacheMap<K, V, F, E> where K: Eq + Hash + Clone, F: AsyncFnMut(&[K]) -> Result<HashMap<K, V>, E> {
+    /// Create a new empty keyed cache.
+    /// `ttl` is "time to live" which is duration that each cached value will be return.
+    /// `refresh_fn` is a batch function to load the value of many keys at once.
+    pub fn new(ttl: Duration, refresh_fn: F) -> CacheMap<K, V, F, E> {
+        CacheMap {
+            ttl,
+            entries: HashMap::new(),
+            usage: BTreeMap::new(),
+            refresh_fn,
+            tick: 0,
+            weigher: None,
+            max_weight: u64::MAX,
+            total_weight: 0,
+            max_batch_size: usize::MAX
+        }
+    }
+    /// Bound the cache by the total weight of its values instead of leaving it unbounded.
+    /// `weigher` compute the weight of each value, e.g. its size in bytes. Whenever the total weight
+    /// exceed `max_weight`, the least recently used entries are evicted until it fit.
+    /// An entry is used when it is inserted, refreshed or read by [CacheMap::get_or_refresh_batch()].
+    /// [CacheMap::get()] doesn't count as use because it only borrow the cache.
+    pub fn with_weigher(mut self, max_weight: u64, weigher: impl Fn(&V) -> u64 + Send + Sync + 'static) -> Self {
+        self.weigher = Some(Box::new(weigher));
+        self.max_weight = max_weight;
+        self.total_weight = self.entries.values().map(|entry| entry.weight).sum();
+        self.evict();
+        self
+    }
+    /// Limit the number of keys given to a single `refresh_fn` call to `max_batch_size`. When more keys need
+    /// to be refreshed, they are split into several batches. The batches are loaded one after another unless
+    /// [CacheMap::get_or_refresh_batch_concurrent()] is used. `max_batch_size` of zero is raised to one.
+    pub fn with_max_batch_size(mut self, max_batch_size: usize) -> Self {
+        self.max_batch_size = max_batch_size.max(1);
+        self
+    }
+    /// Total weight of every cached value as computed by the weigher given to [CacheMap::with_weigher()].
+    pub fn total_weight(&self) -> u64 {
+        self.total_weight
+    }
+    fn next_tick(&mut self) -> u64 {
+        self.tick += 1;
+        self.tick
+    }
+    /// Store `obj` as fresh value of `key` then evict entries if the cache is overweight.
+    fn store(&mut self, key: K, obj: V) -> Option<V> {
+        let weight = self.weigher.as_ref().map_or(0, |weigher| weigher(&obj));
+        let last_used = self.next_tick();
+        let entry = Entry {
+            obj,
+            last_update: clock::now(),
+            last_used,
+            weight
+        };
+        self.total_weight += weight;
+        self.usage.insert(last_used, key.clone());
+        let previous = self.entries.insert(key, entry).map(|entry| {
+            self.usage.remove(&entry.last_used);
+            self.total_weight -= entry.weight;
+            entry.obj
+        });
+        self.evict();
+        previous
+    }
+    /// Evict least recently used entries until total weight fit within `max_weight`.
+    fn evict(&mut self) {
+        while self.total_weight > self.max_weight {
+            let Some((_, key)) = self.usage.pop_first() else {
+                break
+            };
+            if let Some(entry) = self.entries.remove(&key) {
+                self.total_weight -= entry.weight;
+            }
+        }
+    }
+    /// Insert or replace the value of `key`. The value is valid for full `ttl`.
+    /// Return previous value if any.
+    pub fn insert(&mut self, key: K, obj: V) -> Option<V> {
+        self.store(key, obj)
+    }
+    /// Remove `key` from the cache and return its value if any.
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        self.entries.remove(key).map(|entry| {
+            self.usage.remove(&entry.last_used);
+            self.total_weight -= entry.weight;
+            entry.obj
+        })
+    }
+    /// Read cached value of `key`. Return `None` if `key` isn't cached or it is already expired.
+    pub fn get(&self, key: &K) -> Option<&V> {
+        self.entries.get(key).filter(|entry| !entry.is_expired(self.ttl)).map(|entry| &entry.obj)
+    }
+    /// Number of cached entries, including the expired one.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+    /// Check whether there's no cached entry.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+    /// Get the entry of `key` to inspect, refresh or insert its value in place, like [HashMap::entry()].
+    /// An expired entry is still occupied, see [OccupiedEntry::is_expired()].
+    /// ```rust
+    /// use std::collections::HashMap;
+    /// use std::time::Duration;
+    /// use generic_cache::{CacheEntry, CacheMap};
+    ///
+    /// # tokio_test::block_on(async {
+    /// let mut cached = CacheMap::new(Duration::from_secs(1), async |keys: &[u16]| {
+    ///     Ok::<HashMap<u16, u16>, ()>(keys.iter().map(|k| (*k, k * 2)).collect())
+    /// });
+    /// assert_eq!(cached.entry(1).or_insert(10), Some(&10));
+    /// match cached.entry(1) {
+    ///     CacheEntry::Occupied(entry) if !entry.is_expired() => assert_eq!(entry.get(), &10),
+    ///     entry => assert_eq!(entry.refresh().await.unwrap(), Some(&2))
+    /// }
+    /// # })
+    /// ```
+    pub fn entry(&mut self, key: K) -> CacheEntry<'_, K, V, F, E> {
+        if self.entries.contains_key(&key) {
+            CacheEntry::Occupied(OccupiedEntry { map: self, key })
+        } else {
+            CacheEntry::Vacant(VacantEntry { map: self, key })
+        }
+    }
+    /// Remove every entry from the cache and yield its key and current value, including the expired one.
+    /// This is useful to migrate or persist all entries.
+    pub fn drain(&mut self) -> impl Iterator<Item = (K, V)> + '_ {
+        self.total_weight = 0;
+        self.usage.clear();
+        self.entries.drain().map(|(key, entry)| (key, entry.obj))
+    }
+    /// Call `refresh_fn` once per batch of at most `max_batch_size` keys then store every returned value.
+    /// It stop at the first failed batch. Value of the previous batches are kept.
+    async fn load(&mut self, keys: &[K]) -> Result<(), E> {
+        for batch in keys.chunks(self.max_batch_size) {
+            for (key, obj) in (self.refresh_fn)(batch).await? {
+                self.store(key, obj);
+            }
+        }
+        Ok(())
+    }
+    /// Refresh every expired entry with one `refresh_fn` call per batch of at most `max_batch_size` keys,
+    /// see [CacheMap::with_max_batch_size()]. The batches are loaded one after another.
+    pub async fn refresh_expired(&mut self) -> Result<(), E> {
+        let expired: Vec<K> = self.entries.iter().filter(|(_, entry)| entry.is_expired(self.ttl)).map(|(key, _)| key.clone()).collect();
+        self.load(&expired).await
+    }
+    /// Read cached value of every given key. All keys that are missing or expired are loaded before returning
+    /// with one `refresh_fn` call per batch of at most `max_batch_size` keys, which is a single call unless
+    /// [CacheMap::with_max_batch_size()] is used. The batches are loaded one after another because `refresh_fn`
+    /// need `&mut` access. Only [CacheMap::get_or_refresh_batch_concurrent()] load them concurrently.
+    /// The result is in the same order as `keys`. It is `None` if `refresh_fn` didn't return the key.
+    pub async fn get_or_refresh_batch(&mut self, keys: &[K]) -> Result<Vec<Option<&V>>, E> {
+        let stale = self.stale_keys(keys);
+        self.load(&stale).await?;
+        Ok(keys.iter().map(|key| self.get(key)).collect())
+    }
+    /// Mark every given key that is still valid as used then return the missing or expired ones without duplicate.
+    fn stale_keys(&mut self, keys: &[K]) -> Vec<K> {
+        let mut stale = Vec::new();
+        for key in keys {
+            let tick = self.next_tick();
+            match self.entries.get_mut(key) {
+                Some(entry) if !entry.is_expired(self.ttl) => {
+                    self.usage.remove(&entry.last_used);
+                    self.usage.insert(tick, key.clone());
+                    entry.last_used = tick;
+                },
+                _ if !stale.contains(key) => stale.push(key.clone()),
+                _ => ()
+            }
+        }
+        stale
+    }
+}
+/// A view into a single key of a [CacheMap] returned by [CacheMap::entry()].
+pub enum CacheEntry<'a, K, V, F, E> where F: AsyncFnMut(&[K]) -> Result<HashMap<K, V>, E> {
+    /// The key is cached, either valid or expired.
+    Occupied(OccupiedEntry<'a, K, V, F, E>),
+    /// The key isn't cached.
+    Vacant(VacantEntry<'a, K, V, F, E>)
+}
+impl<'a, K, V, F, E> CacheEntry<'a, K, V, F, E> where K: Eq + Hash + Clone, F: AsyncFnMut(&[K]) -> Result<HashMap<K, V>, E> {
+    /// Key of this entry.
+    pub fn key(&self) -> &K {
+        match self {
+            CacheEntry::Occupied(entry) => entry.key(),
+            CacheEntry::Vacant(entry) => entry.key()
+        }
+    }
+    /// Insert `obj` if the key isn't cached then return the cached value, see [VacantEntry::insert()].
+    /// An expired value is returned as is.
+    pub fn or_insert(self, obj: V) -> Option<&'a V> {
+        match self {
+            CacheEntry::Occupied(entry) => Some(entry.into_ref()),
+            CacheEntry::Vacant(entry) => entry.insert(obj)
+        }
+    }
+    /// Load the value of the key with a `refresh_fn` call whether it is cached or not.
+    /// Return `None` if the key isn't cached after the call, i.e. `refresh_fn` didn't return it.
+    pub async fn refresh(self) -> Result<Option<&'a V>, E> {
+        match self {
+            CacheEntry::Occupied(entry) => entry.refresh().await,
+            CacheEntry::Vacant(entry) => entry.refresh().await
+        }
+    }
+}
+/// A key that is cached in a [CacheMap], see [CacheEntry].
+/// The key stay cached as long as this entry exist. Every method that may evict it consume the entry.
+pub struct OccupiedEntry<'a, K, V, F, E> where F: AsyncFnMut(&[K]) -> Result<HashMap<K, V>, E> {
+    map: &'a mut CacheMap<K, V, F, E>,
+    key: K
+}
+impl<'a, K, V, F, E> OccupiedEntry<'a, K, V, F, E> where K: Eq + Hash + Clone, F: AsyncFnMut(&[K]) -> Result<HashMap<K, V>, E> {
+    fn entry(&self) -> &Entry<V> {
+        &self.map.entries[&self.key]
+    }
+    /// Key of this entry.
+    pub fn key(&self) -> &K {
+        &self.key
+    }
+    /// Cached value, even if it is expired.
+    pub fn get(&self) -> &V {
+        &self.entry().obj
+    }
+    /// Convert into the cached value bound to the lifetime of the cache.
+    pub fn into_ref(self) -> &'a V {
+        &self.map.entries[&self.key].obj
+    }
+    /// Check whether the cached value is expired.
+    pub fn is_expired(&self) -> bool {
+        self.entry().is_expired(self.map.ttl)
+    }
+    /// Replace the cached value with `obj`, valid for full `ttl`, and return the previous value.
+    /// The entry is consumed because `obj` is evicted right away if it alone exceed the max weight of
+    /// [CacheMap::with_weigher()]. Use [CacheMap::entry()] again to keep working on the key.
+    pub fn insert(self, obj: V) -> V {
+        self.map.store(self.key, obj).expect("the entry is occupied")
+    }
+    /// Remove the entry from the cache and return its value.
+    pub fn remove(self) -> V {
+        self.map.remove(&self.key).expect("the entry is occupied")
+    }
+    /// Load the value of the key with a `refresh_fn` call, even if it isn't expired.
+    /// The cached value is kept if `refresh_fn` didn't return the key.
+    pub async fn refresh(self) -> Result<Option<&'a V>, E> {
+        self.map.load(std::slice::from_ref(&self.key)).await?;
+        Ok(self.map.entries.get(&self.key).map(|entry| &entry.obj))
+    }
+}
+/// A key that isn't cached in a [CacheMap], see [CacheEntry].
+pub struct VacantEntry<'a, K, V, F, E> where F: AsyncFnMut(&[K]) -> Result<HashMap<K, V>, E> {
+    map: &'a mut CacheMap<K, V, F, E>,
+    key: K
+}
+impl<'a, K, V, F, E> VacantEntry<'a, K, V, F, E> where K: Eq + Hash + Clone, F: AsyncFnMut(&[K]) -> Result<HashMap<K, V>, E> {
+    /// Key of this entry.
+    pub fn key(&self) -> &K {
+        &self.key
+    }
+    /// Insert `obj` as value of the key, valid for full `ttl`, then return it.
+    /// Return `None` if it is evicted right away because it alone exceed the max weight of [CacheMap::with_weigher()].
+    pub fn insert(self, obj: V) -> Option<&'a V> {
+        self.map.store(self.key.clone(), obj);
+        self.map.entries.get(&self.key).map(|entry| &entry.obj)
+    }
+    /// Load the value of the key with a `refresh_fn` call.
+    /// Return `None` if `refresh_fn` didn't return the key.
+    pub async fn refresh(self) -> Result<Option<&'a V>, E> {
+        self.map.load(std::slice::from_ref(&self.key)).await?;
+        Ok(self.map.entries.get(&self.key).map(|entry| &entry.obj))
+    }
+}
+/// Batches split by [CacheMap::with_max_batch_size()] can be loaded concurrently when `refresh_fn` can be
+/// called through a shared reference.
+impl<K, V, F, E> CacheMap<K, V, F, E> where K: Eq + Hash + Clone, F: AsyncFn(&[K]) -> Result<HashMap<K, V>, E> {
+    /// Same as [CacheMap::get_or_refresh_batch()] but every batch of at most `max_batch_size` keys is loaded
+    /// concurrently. If some batches fail, the value of the successful ones are still stored and the error
+    /// of the first failed batch is returned. The concurrency doesn't depend on any async runtime.
+    pub async fn get_or_refresh_batch_concurrent(&mut self, keys: &[K]) -> Result<Vec<Option<&V>>, E> {
+        let stale = self.stale_keys(keys);
+        let results = join_all(stale.chunks(self.max_batch_size).map(|batch| (self.refresh_fn)(batch)).collect()).await;
+        let mut error = None;
+        for result in results {
+            match result {
+                Ok(objs) => for (key, obj) in objs {
+                    self.store(key, obj);
+                },
+                Err(e) => if error.is_none() {
+                    error = Some(e);
+                }
+            }
+        }
+        if let Some(e) = error {
+            return Err(e)
+        }
+        Ok(keys.iter().map(|key| self.get(key)).collect())
+    }
+}
+/// Poll every future concurrently on the current task until all of them are done.
+/// The outputs are in the same order as `futures`.
+async fn join_all<Fut: Future>(futures: Vec<Fut>) -> Vec<Fut::Output> {
+    let mut futures: Vec<_> = futures.into_iter().map(Box::pin).collect();
+    let mut outputs: Vec<Option<Fut::Output>> = futures.iter().map(|_| None).collect();
+    poll_fn(|cx| {
+        for (future, output) in futures.iter_mut().zip(outputs.iter_mut()) {
+            if output.is_none() {
+                if let Poll::Ready(obj) = future.as_mut().poll(cx) {
+                    *output = Some(obj);
+                }
+            }
+        }
+        if outputs.iter().all(Option::is_some) {
+            Poll::Ready(outputs.drain(..).flatten().collect())
+        } else {
+            Poll::Pending
+        }
+    }).await
+}
+#[cfg(test)]
+mod tests {
+    use std::thread::sleep;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn batch_refresh() {
+        let mut calls: Vec<Vec<u16>> = Vec::new();
+        let mut cached = CacheMap::new(Duration::from_millis(20), async |keys: &[u16]| {
+            calls.push(keys.to_vec());
+            Ok::<HashMap<u16, u16>, ()>(keys.iter().map(|k| (*k, k * 10)).collect())
+        });
+        cached.insert(1, 1);
+        cached.insert(2, 2);
+        cached.insert(3, 3);
+        sleep(Duration::from_millis(30));
+        cached.insert(4, 4);
+        let values = cached.get_or_refresh_batch(&[1, 2, 3, 4, 5]).await.unwrap();
+        assert_eq!(values, vec![Some(&10), Some(&20), Some(&30), Some(&4), Some(&50)], "Expect expired and missing keys to be refreshed");
+        drop(cached);
+        assert_eq!(calls.len(), 1, "Expect a single batch call but got {:?}", calls);
+        let mut batch = calls[0].clone();
+        batch.sort();
+        assert_eq!(batch, vec![1, 2, 3, 5], "Expect batch loader to receive all expired and missing keys at once");
+    }
+    #[tokio::test]
+    async fn batch_omit_expired_key() {
+        let mut cached = CacheMap::new(Duration::from_millis(20), async |keys: &[u16]| {
+            Ok::<HashMap<u16, u16>, ()>(keys.iter().filter(|k| **k != 1).map(|k| (*k, k * 10)).collect())
+        });
+        cached.insert(1, 1);
+        cached.insert(2, 2);
+        sleep(Duration::from_millis(30));
+        let values = cached.get_or_refresh_batch(&[1, 2]).await.unwrap();
+        assert_eq!(values, vec![None, Some(&20)], "Expect expired key that refresh_fn didn't return to be None");
+    }
+    #[test]
+    fn weighed_eviction() {
+        let mut cached = CacheMap::new(Duration::from_secs(60), async |_: &[u16]| {Ok::<HashMap<u16, Vec<u8>>, ()>(HashMap::new())})
+            .with_weigher(10, |v: &Vec<u8>| v.len() as u64);
+        for key in 0..6 {
+            cached.insert(key, vec![0u8]);
+        }
+        assert_eq!(cached.len(), 6, "Expect entry count alone to never trigger eviction");
+        assert_eq!(cached.total_weight(), 6, "Expect {} to equals {}", cached.total_weight(), 6);
+        cached.insert(6, vec![0u8; 8]);
+        assert_eq!(cached.total_weight(), 10, "Expect total weight to fit within max weight");
+        assert_eq!(cached.len(), 3, "Expect {} to equals {}", cached.len(), 3);
+        for evicted in 0..4 {
+            assert_eq!(cached.get(&evicted), None, "Expect least recently used key {} to be evicted", evicted);
+        }
+        assert!(cached.get(&4).is_some() && cached.get(&5).is_some() && cached.get(&6).is_some(), "Expect most recently used keys to be kept");
+    }
+    #[tokio::test]
+    async fn evict_least_recently_used() {
+        let mut cached = CacheMap::new(Duration::from_secs(60), async |_: &[u16]| {Ok::<HashMap<u16, u16>, ()>(HashMap::new())})
+            .with_weigher(3, |_| 1);
+        for key in 1..=3 {
+            cached.insert(key, key);
+        }
+        cached.get_or_refresh_batch(&[1]).await.unwrap();
+        cached.insert(4, 4);
+        assert_eq!(cached.get(&2), None, "Expect least recently used key to be evicted");
+        assert!(cached.get(&1).is_some(), "Expect key used by a batch read to be kept");
+        cached.remove(&3);
+        cached.insert(1, 10);
+        cached.insert(5, 5);
+        cached.insert(6, 6);
+        assert_eq!(cached.get(&4), None, "Expect replaced key to count as used");
+        assert_eq!((cached.get(&1), cached.get(&5), cached.get(&6)), (Some(&10), Some(&5), Some(&6)), "Expect most recently used keys to be kept");
+        assert_eq!(cached.total_weight(), 3, "Expect {} to equals {}", cached.total_weight(), 3);
+    }
+    #[tokio::test]
+    async fn refresh_all_expired() {
+        let mut calls = 0;
+        let mut cached = CacheMap::new(Duration::from_millis(20), async |keys: &[u16]| {
+            calls += 1;
+            Ok::<HashMap<u16, u16>, ()>(keys.iter().map(|k| (*k, k * 10)).collect())
+        });
+        cached.insert(1, 1);
+        cached.insert(2, 2);
+        sleep(Duration::from_millis(30));
+        assert_eq!(cached.get(&1), None, "Expect expired entry to be hidden");
+        cached.refresh_expired().await.unwrap();
+        assert_eq!(cached.get(&1), Some(&10), "Expect {:?} to equals {:?}", cached.get(&1), Some(&10));
+        assert_eq!(cached.get(&2), Some(&20), "Expect {:?} to equals {:?}", cached.get(&2), Some(&20));
+        drop(cached);
+        assert_eq!(calls, 1, "Expect all expired keys to be refreshed in one call");
+    }
+    #[test]
+    fn drain_all_entries() {
+        let mut cached = CacheMap::new(Duration::from_secs(60), async |_: &[u16]| {Ok::<HashMap<u16, Vec<u8>>, ()>(HashMap::new())})
+            .with_weigher(100, |v: &Vec<u8>| v.len() as u64);
+        cached.insert(1, vec![1]);
+        cached.insert(2, vec![2, 2]);
+        let mut drained: Vec<(u16, Vec<u8>)> = cached.drain().collect();
+        drained.sort();
+        assert_eq!(drained, vec![(1, vec![1]), (2, vec![2, 2])], "Expect every entry to be yielded");
+        assert!(cached.is_empty(), "Expect cache to be empty after drain");
+        assert_eq!(cached.total_weight(), 0, "Expect {} to equals {}", cached.total_weight(), 0);
+    }
+    #[tokio::test]
+    async fn bounded_concurrent_batches() {
+        let calls = std::cell::RefCell::new(Vec::new());
+        let running = std::cell::Cell::new(0);
+        let max_running = std::cell::Cell::new(0);
+        let load = async |keys: &[u16]| {
+            calls.borrow_mut().push(keys.to_vec());
+            running.set(running.get() + 1);
+            max_running.set(max_running.get().max(running.get()));
+            tokio::task::yield_now().await;
+            running.set(running.get() - 1);
+            Ok::<HashMap<u16, u16>, ()>(keys.iter().map(|k| (*k, k * 10)).collect())
+        };
+        let mut cached = CacheMap::new(Duration::from_millis(20), load).with_max_batch_size(3);
+        let keys: Vec<u16> = (0..10).collect();
+        for key in &keys {
+            cached.insert(*key, *key);
+        }
+        sleep(Duration::from_millis(30));
+        let values = cached.get_or_refresh_batch_concurrent(&keys).await.unwrap();
+        let expected: Vec<u16> = keys.iter().map(|k| k * 10).collect();
+        assert_eq!(values, expected.iter().map(Some).collect::<Vec<_>>(), "Expect every expired key to be refreshed");
+        drop(cached);
+        let calls = calls.into_inner();
+        assert_eq!(calls.len(), 4, "Expect keys to be split into 4 batches but got {:?}", calls);
+        assert!(calls.iter().all(|batch| batch.len() <= 3), "Expect no batch larger than 3 but got {:?}", calls);
+        assert_eq!(calls.iter().map(Vec::len).sum::<usize>(), 10, "Expect every key to be loaded once but got {:?}", calls);
+        assert_eq!(max_running.get(), 4, "Expect batches to be loaded concurrently");
+    }
+    #[tokio::test]
+    async fn concurrent_batch_omit_expired_key() {
+        let load = async |keys: &[u16]| {
+            Ok::<HashMap<u16, u16>, ()>(keys.iter().filter(|k| **k != 1).map(|k| (*k, k * 10)).collect())
+        };
+        let mut cached = CacheMap::new(Duration::from_millis(20), load).with_max_batch_size(1);
+        cached.insert(1, 1);
+        cached.insert(2, 2);
+        sleep(Duration::from_millis(30));
+        let values = cached.get_or_refresh_batch_concurrent(&[1, 2]).await.unwrap();
+        assert_eq!(values, vec![None, Some(&20)], "Expect expired key that refresh_fn didn't return to be None");
+    }
+    #[tokio::test]
+    async fn entry_insert_when_absent() {
+        let mut calls = 0;
+        let mut cached = CacheMap::new(Duration::from_millis(20), async |keys: &[u16]| {
+            calls += 1;
+            Ok::<HashMap<u16, u16>, ()>(keys.iter().map(|k| (*k, k * 10)).collect())
+        });
+        cached.insert(1, 1);
+        for key in [1, 2] {
+            if let CacheEntry::Vacant(entry) = cached.entry(key) {
+                assert_eq!(entry.insert(key * 100), Some(&200), "Expect only absent key to be inserted");
+            }
+        }
+        assert_eq!((cached.get(&1), cached.get(&2)), (Some(&1), Some(&200)), "Expect cached key to be left untouched");
+        sleep(Duration::from_millis(30));
+        match cached.entry(1) {
+            CacheEntry::Occupied(entry) => {
+                assert!(entry.is_expired(), "Expect entry to be expired");
+                assert_eq!(entry.refresh().await.unwrap(), Some(&10), "Expect expired entry to be refreshed in place");
+            },
+            CacheEntry::Vacant(_) => panic!("Expect expired key to be occupied")
+        }
+        drop(cached);
+        assert_eq!(calls, 1, "Expect a single refresh call but got {}", calls);
+    }
+    #[test]
+    fn entry_insert_overweight() {
+        let mut cached = CacheMap::new(Duration::from_secs(60), async |_: &[u16]| {Ok::<HashMap<u16, Vec<u8>>, ()>(HashMap::new())})
+            .with_weigher(4, |v: &Vec<u8>| v.len() as u64);
+        cached.insert(1, vec![1]);
+        let CacheEntry::Occupied(entry) = cached.entry(1) else {
+            panic!("Expect inserted key to be occupied")
+        };
+        assert_eq!(entry.insert(vec![2; 5]), vec![1], "Expect previous value to be returned");
+        assert!(matches!(cached.entry(1), CacheEntry::Vacant(_)), "Expect value heavier than max weight to be evicted");
+        assert_eq!(cached.entry(2).or_insert(vec![3; 5]), None, "Expect vacant insert heavier than max weight to be evicted");
+        assert_eq!(cached.total_weight(), 0, "Expect {} to equals {}", cached.total_weight(), 0);
+    }
+}
@@ -0,0 +1,67 @@
+//! One shot memoization without constructing a cache object.
+use std::sync::{Mutex, PoisonError};
+use std::time::{Duration, SystemTime};
+
+use crate::clock;
+
+/// A store for [cache_or_compute]. It can be created in `const` context so it can be a `static`.
+/// ```rust
+/// use std::time::Duration;
+/// use generic_cache::{cache_or_compute, Memo};
+///
+/// static TOKEN: Memo<String> = Memo::new();
+/// # tokio_test::block_on(async {
+/// let token = cache_or_compute(&TOKEN, Duration::from_secs(60), async || {Ok::<String, ()>("abc".to_string())}).await.unwrap();
+/// assert_eq!(token, "abc");
+/// # })
+/// ```
+pub struct Memo<T> {
+    slot: Mutex<Option<(T, SystemTime)>>
+}
+impl<T> Memo<T> {
+    /// Create an empty store.
+    pub const fn new() -> Memo<T> {
+        Memo {
+            slot: Mutex::new(None)
+        }
+    }
+}
+impl<T> Default for Memo<T> {
+    fn default() -> Self {
+        Memo::new()
+    }
+}
+
+/// Return the value stored in `store` if it is younger than `ttl`. Otherwise, call `loader`
+/// then store and return the new value.
+/// The store is not locked while `loader` is running so concurrent callers may call `loader` more than once.
+/// If `loader` fail, the store is left untouched.
+pub async fn cache_or_compute<T, E>(store: &Memo<T>, ttl: Duration, loader: impl AsyncFnOnce() -> Result<T, E>) -> Result<T, E> where T: Clone {
+    if let Some((obj, last_update)) = &*store.slot.lock().unwrap_or_else(PoisonError::into_inner) {
+        if clock::elapsed(*last_update) <= ttl {
+            return Ok(obj.clone())
+        }
+    }
+    let obj = loader().await?;
+    *store.slot.lock().unwrap_or_else(PoisonError::into_inner) = Some((obj.clone(), clock::now()));
+    Ok(obj)
+}
+#[cfg(test)]
+mod tests {
+    use std::thread::sleep;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn memoize_until_expired() {
+        static STORE: Memo<u16> = Memo::new();
+        let mut count = 0u16;
+        let first = cache_or_compute(&STORE, Duration::from_millis(50), async || {count += 1; Ok::<u16, ()>(count)}).await.unwrap();
+        let second = cache_or_compute(&STORE, Duration::from_millis(50), async || {count += 1; Ok::<u16, ()>(count)}).await.unwrap();
+        assert_eq!(first, second, "Expect {} to equals {}", first, second);
+        sleep(Duration::from_millis(60));
+        let third = cache_or_compute(&STORE, Duration::from_millis(50), async || {count += 1; Ok::<u16, ()>(count)}).await.unwrap();
+        assert_eq!(third, 2, "Expect value to be recomputed after expired");
+        assert_eq!(count, 2, "loader should be called twice");
+    }
+}
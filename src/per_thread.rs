@@ -0,0 +1,163 @@
+//! A cache where every thread has its own cached object.
+use std::any::Any;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::marker::PhantomData;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+use crate::{Object, TimeoutError};
+
+static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+
+thread_local! {
+    static SLOTS: RefCell<HashMap<u64, Box<dyn Any>>> = RefCell::new(HashMap::new());
+}
+
+/// Cache where each thread lazily get its own [Object] built from the same `ttl`, initial value and `refresh_fn`.
+/// Reading never synchronize with other threads which suit read heavy thread per core architecture.
+///
+/// The trade-off is that the value is cached once per thread so N threads mean N copies of the value
+/// and N calls to `refresh_fn` per `ttl`. Use [crate::SharedCache] if that is too expensive.
+///
+/// The value is returned as a clone because it cannot borrow from thread local storage.
+/// While a refresh is running, the [Object] of the current thread is taken out so another call on the same
+/// thread during that time use a fresh copy of the initial value. It is put back once the refresh is done or
+/// its future is dropped. Dropping this cache only free the copy of the current thread. Copies of other
+/// threads are freed when those threads exit.
+///
+/// A future that refresh isn't [Send] so it always resume on the thread that started it. It can be awaited
+/// directly, e.g. on a `current_thread` runtime, or spawned with `tokio::task::spawn_local` on a
+/// `LocalSet`, but not with `tokio::spawn` on a multi-thread runtime where it could move to another worker.
+/// ```rust
+/// use std::time::Duration;
+/// use generic_cache::ThreadLocalCache;
+///
+/// # tokio_test::block_on(async {
+/// let cached = ThreadLocalCache::new(Duration::from_secs(1), 100, async || {Ok::<u16, ()>(200)});
+/// assert_eq!(cached.get().unwrap(), 100);
+/// cached.refresh().await.unwrap();
+/// assert_eq!(cached.get().unwrap(), 200);
+/// # })
+/// ```
+pub struct ThreadLocalCache<T, F, E = ()> {
+    id: u64,
+    ttl: Duration,
+    obj: T,
+    refresh_fn: F,
+    _error: std::marker::PhantomData<fn() -> E>
+}
+impl<T, F, E> ThreadLocalCache<T, F, E> where T: Clone + 'static, F: AsyncFnMut() -> Result<T, E> + Clone + 'static, E: 'static {
+    /// Create a new cache. Each thread start with a clone of `obj` and `refresh_fn`. See [Object::new()].
+    pub fn new(ttl: Duration, obj: T, refresh_fn: F) -> ThreadLocalCache<T, F, E> {
+        ThreadLocalCache {
+            id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
+            ttl,
+            obj,
+            refresh_fn,
+            _error: std::marker::PhantomData
+        }
+    }
+    /// Run `f` with the [Object] of the current thread, creating it if needed.
+    fn with<R>(&self, f: impl FnOnce(&mut Object<T, F, E>) -> R) -> R {
+        SLOTS.with_borrow_mut(|slots| {
+            let slot = slots.entry(self.id).or_insert_with(|| Box::new(self.create()));
+            f(slot.downcast_mut().expect("slot keyed by cache id"))
+        })
+    }
+    fn create(&self) -> Object<T, F, E> {
+        Object::new(self.ttl, self.obj.clone(), self.refresh_fn.clone())
+    }
+    /// Take the [Object] of the current thread out so it can be used across `.await`.
+    fn take(&self) -> Taken<T, F, E> {
+        let obj = SLOTS.with_borrow_mut(|slots| match slots.remove(&self.id).map(|slot| slot.downcast()) {
+            Some(Ok(obj)) => *obj,
+            _ => self.create()
+        });
+        Taken {
+            id: self.id,
+            obj: Some(obj),
+            _not_send: PhantomData
+        }
+    }
+    /// Refresh the cache of the current thread immediately.
+    pub async fn refresh(&self) -> Result<(), E> {
+        self.take().obj().refresh().await
+    }
+    /// Read a clone of the value cached by the current thread or return Error if it is already expired.
+    pub fn get(&self) -> Result<T, TimeoutError> {
+        self.with(|obj| obj.get().cloned())
+    }
+    /// Read a clone of the value cached by the current thread or refresh it if it is already expired.
+    pub async fn get_or_refresh(&self) -> Result<T, E> {
+        if let Ok(obj) = self.get() {
+            return Ok(obj)
+        }
+        self.take().obj().get_or_refresh().await.cloned()
+    }
+    /// Number of time `refresh_fn` was called by the current thread.
+    pub fn refresh_count(&self) -> u64 {
+        self.with(|obj| obj.refresh_count())
+    }
+}
+/// [Object] taken out of the slot of the current thread by [ThreadLocalCache]. It is put back into the same
+/// slot when dropped. It isn't [Send] so the future holding it across `.await` can't move to another thread.
+struct Taken<T: 'static, F: 'static, E: 'static> {
+    id: u64,
+    obj: Option<Object<T, F, E>>,
+    _not_send: PhantomData<*const ()>
+}
+impl<T, F, E> Taken<T, F, E> {
+    fn obj(&mut self) -> &mut Object<T, F, E> {
+        self.obj.as_mut().expect("object is only put back on drop")
+    }
+}
+impl<T: 'static, F: 'static, E: 'static> Drop for Taken<T, F, E> {
+    fn drop(&mut self) {
+        if let Some(obj) = self.obj.take() {
+            let _ = SLOTS.try_with(|slots| slots.borrow_mut().insert(self.id, Box::new(obj)));
+        }
+    }
+}
+impl<T, F, E> Drop for ThreadLocalCache<T, F, E> {
+    fn drop(&mut self) {
+        let _ = SLOTS.try_with(|slots| slots.borrow_mut().remove(&self.id));
+    }
+}
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use super::*;
+
+    #[test]
+    fn independent_threads() {
+        let cached = ThreadLocalCache::new(Duration::from_secs(60), 0u16, async || {Ok::<u16, ()>(1)});
+        let shared = &cached;
+        thread::scope(|scope| {
+            scope.spawn(move || {
+                tokio_test::block_on(shared.refresh()).unwrap();
+                assert_eq!(shared.get().unwrap(), 1, "Expect refresh to be visible on the same thread");
+            }).join().unwrap();
+            scope.spawn(move || {
+                assert_eq!(shared.get().unwrap(), 0, "Expect other thread to keep its own value");
+                assert_eq!(shared.refresh_count(), 0, "Expect no refresh on this thread");
+            });
+        });
+        assert_eq!(cached.get().unwrap(), 0, "Expect {} to equals {}", cached.get().unwrap(), 0);
+    }
+    #[test]
+    fn put_back_on_cancel() {
+        let cached = ThreadLocalCache::new(Duration::ZERO, 0u16, async || {
+            tokio::task::yield_now().await;
+            Ok::<u16, ()>(1)
+        });
+        tokio_test::block_on(cached.refresh()).unwrap();
+        assert_eq!(cached.refresh_count(), 1, "Expect {} to equals {}", cached.refresh_count(), 1);
+        let mut refresh = Box::pin(cached.get_or_refresh());
+        let poll = tokio_test::task::spawn(()).enter(|cx, _| refresh.as_mut().poll(cx));
+        assert!(poll.is_pending(), "Expect refresh to be in progress");
+        drop(refresh);
+        assert_eq!(cached.refresh_count(), 2, "Expect object to be put back when the refresh future is dropped");
+    }
+}
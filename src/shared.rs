@@ -0,0 +1,551 @@
+//! A cached object that can be shared between threads and tasks.
+use std::future::{poll_fn, Future};
+use std::marker::PhantomData;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::pin::pin;
+#[cfg(feature = "tokio")]
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, PoisonError, RwLock, RwLockReadGuard};
+use std::task::Poll;
+use std::time::{Duration, SystemTime};
+
+#[cfg(feature = "tokio")]
+use tokio::sync::Notify;
+#[cfg(feature = "tokio")]
+use tokio::task::JoinHandle;
+
+use crate::{clock, CacheValue, RefreshError, TimeoutError};
+
+struct Entry<T> {
+    obj: Arc<T>,
+    last_update: SystemTime
+}
+struct Inner<T, F> {
+    ttl: Duration,
+    entry: RwLock<Entry<T>>,
+    refresh_fn: F,
+    refresh_count: AtomicU64,
+    generation: AtomicU64,
+    #[cfg(feature = "tokio")]
+    background_refresh: AtomicBool,
+    #[cfg(feature = "tokio")]
+    background_generation: AtomicU64,
+    #[cfg(feature = "tokio")]
+    background_done: Notify,
+    #[cfg(feature = "tokio")]
+    refreshed: Notify
+}
+/// Mark the background refresh of [SharedCache::prefetch()] as done and wake its waiters when dropped,
+/// even if `refresh_fn` panicked.
+#[cfg(feature = "tokio")]
+struct BackgroundRefresh<'a, T, F> {
+    inner: &'a Inner<T, F>
+}
+#[cfg(feature = "tokio")]
+impl<T, F> Drop for BackgroundRefresh<'_, T, F> {
+    fn drop(&mut self) {
+        self.inner.background_refresh.store(false, Ordering::Release);
+        self.inner.background_done.notify_waiters();
+    }
+}
+
+/// Thread safe cached object which can be cloned and shared between threads and tasks.
+/// Every clone point to the same cached value.
+///
+/// Unlike [crate::Object], every method only take `&self` and the value is returned as [Arc] so
+/// it can outlive the internal lock.
+/// The `refresh_fn` is a function returning `Future` that resolve to the same type as the cached object.
+///
+/// # Read during refresh
+/// A refresh never hold the lock while `refresh_fn` is running. Any reader calling [SharedCache::get]
+/// during the refresh get the previous value immediately instead of waiting for the refresh.
+/// Once `refresh_fn` finished, the new value and its last update time are swapped in under a single
+/// write lock so reader either see the previous value or the new value but never a partial one.
+/// If `refresh_fn` fail, the previous value is kept.
+///
+/// # Panic in refresh
+/// Since no lock is held while `refresh_fn` is running, a panicking `refresh_fn` never poison the cache.
+/// The previous value is kept and other clones keep working. Use [SharedCache::refresh_catching_panic()]
+/// to get the panic back as [RefreshError::Panicked] instead of unwinding the caller.
+///
+/// ```rust
+/// use std::time::Duration;
+/// use generic_cache::SharedCache;
+///
+/// # tokio_test::block_on(async {
+/// let cached = SharedCache::new(Duration::from_secs(1), 100, || async {Ok::<u16, ()>(200)});
+/// let other = cached.clone();
+/// other.refresh().await.unwrap();
+/// assert_eq!(*cached.get().unwrap(), 200);
+/// # })
+/// ```
+pub struct SharedCache<T, F, E = ()> {
+    inner: Arc<Inner<T, F>>,
+    #[cfg(feature = "tokio")]
+    grace: Option<Duration>,
+    _error: PhantomData<fn() -> E>
+}
+impl<T, F, E> Clone for SharedCache<T, F, E> {
+    fn clone(&self) -> Self {
+        SharedCache {
+            inner: Arc::clone(&self.inner),
+            #[cfg(feature = "tokio")]
+            grace: self.grace,
+            _error: PhantomData
+        }
+    }
+}
+impl<T, F, Fut, E> SharedCache<T, F, E> where F: Fn() -> Fut, Fut: Future<Output = Result<T, E>> {
+    /// Create a new shared cache with default value specify in second argument.
+    /// `ttl` is "time to live" which is duration that the cached value will be return.
+    /// `refresh_fn` is a function to refresh value and last update time.
+    pub fn new(ttl: Duration, obj: T, refresh_fn: F) -> SharedCache<T, F, E> {
+        SharedCache::from_parts(ttl, obj, clock::now(), refresh_fn, 0)
+    }
+    /// Create a shared cache that continue from an existing state, e.g. from [crate::Object::into_shared()].
+    pub(crate) fn from_parts(ttl: Duration, obj: T, last_update: SystemTime, refresh_fn: F, refresh_count: u64) -> SharedCache<T, F, E> {
+        SharedCache {
+            inner: Arc::new(Inner {
+                ttl,
+                entry: RwLock::new(Entry {
+                    obj: Arc::new(obj),
+                    last_update
+                }),
+                refresh_fn,
+                refresh_count: AtomicU64::new(refresh_count),
+                generation: AtomicU64::new(0),
+                #[cfg(feature = "tokio")]
+                background_refresh: AtomicBool::new(false),
+                #[cfg(feature = "tokio")]
+                background_generation: AtomicU64::new(0),
+                #[cfg(feature = "tokio")]
+                background_done: Notify::new(),
+                #[cfg(feature = "tokio")]
+                refreshed: Notify::new()
+            }),
+            #[cfg(feature = "tokio")]
+            grace: None,
+            _error: PhantomData
+        }
+    }
+    /// Create a new shared cache and immediately refresh the value instead of using default value.
+    pub async fn new_and_refresh(ttl: Duration, refresh_fn: F) -> Result<SharedCache<T, F, E>, E> {
+        let obj = refresh_fn().await?;
+        Ok(SharedCache::from_parts(ttl, obj, clock::now(), refresh_fn, 1))
+    }
+    fn entry(&self) -> RwLockReadGuard<'_, Entry<T>> {
+        self.inner.entry.read().unwrap_or_else(PoisonError::into_inner)
+    }
+    /// Refresh cache immediately and update last update time if refresh success.
+    /// Reader keep getting the previous value until this refresh is done.
+    pub async fn refresh(&self) -> Result<(), E> {
+        self.refresh_current().await.map(|_| ())
+    }
+    /// Same as [SharedCache::refresh()] but the new value is discarded if the cache was invalidated with
+    /// cancellation while `refresh_fn` was running. Return whether the new value was stored.
+    async fn refresh_current(&self) -> Result<bool, E> {
+        let generation = self.inner.generation.load(Ordering::Acquire);
+        self.inner.refresh_count.fetch_add(1, Ordering::Relaxed);
+        let obj = Arc::new((self.inner.refresh_fn)().await?);
+        let mut entry = self.inner.entry.write().unwrap_or_else(PoisonError::into_inner);
+        if self.inner.generation.load(Ordering::Acquire) != generation {
+            return Ok(false)
+        }
+        *entry = Entry {
+            obj,
+            last_update: clock::now()
+        };
+        drop(entry);
+        #[cfg(feature = "tokio")]
+        self.inner.refreshed.notify_waiters();
+        Ok(true)
+    }
+    /// Mark the cached value as expired regardless of `ttl`. The next `get_or_refresh` call will refresh it.
+    /// When `cancel_refresh` is `true`, the result of every refresh in progress, including a background one,
+    /// is discarded once it is done because it may be based on the invalidated state. Caller waiting for a
+    /// background refresh stop waiting and refresh by itself. Otherwise, a refresh in progress still store its value.
+    pub fn invalidate(&self, cancel_refresh: bool) {
+        let mut entry = self.inner.entry.write().unwrap_or_else(PoisonError::into_inner);
+        entry.last_update = SystemTime::UNIX_EPOCH;
+        if cancel_refresh {
+            self.inner.generation.fetch_add(1, Ordering::AcqRel);
+            #[cfg(feature = "tokio")]
+            self.inner.background_done.notify_waiters();
+        }
+    }
+    /// Same as [SharedCache::refresh()] but a panic of `refresh_fn` is caught and returned as
+    /// [RefreshError::Panicked] with the panic message. The previous value is kept.
+    pub async fn refresh_catching_panic(&self) -> Result<(), RefreshError<E>> {
+        let mut refresh = pin!(self.refresh());
+        poll_fn(|cx| match catch_unwind(AssertUnwindSafe(|| refresh.as_mut().poll(cx))) {
+            Ok(Poll::Ready(result)) => Poll::Ready(result.map_err(RefreshError::Refresh)),
+            Ok(Poll::Pending) => Poll::Pending,
+            Err(panic) => {
+                let message = match panic.downcast::<String>() {
+                    Ok(message) => *message,
+                    Err(panic) => panic.downcast_ref::<&str>().map_or("unknown panic", |message| message).to_string()
+                };
+                Poll::Ready(Err(RefreshError::Panicked(message)))
+            }
+        }).await
+    }
+    /// Read current cached value or return Error if cache is already expired.
+    pub fn get(&self) -> Result<Arc<T>, TimeoutError> {
+        let entry = self.entry();
+        if clock::elapsed(entry.last_update) > self.inner.ttl {
+            return Err(TimeoutError {})
+        }
+        Ok(Arc::clone(&entry.obj))
+    }
+    /// Read current cached value or refresh the value if it is already expired then
+    /// return the new value.
+    /// If a background refresh spawned by [SharedCache::get_or_refresh_graceful()] is in progress,
+    /// its result is awaited instead of calling `refresh_fn` again. Only if it didn't bring a valid value,
+    /// e.g. because it failed, this call refresh by itself.
+    pub async fn get_or_refresh(&self) -> Result<Arc<T>, E> {
+        if let Ok(obj) = self.get() {
+            return Ok(obj)
+        }
+        #[cfg(feature = "tokio")]
+        if self.wait_background_refresh().await {
+            if let Ok(obj) = self.get() {
+                return Ok(obj)
+            }
+        }
+        while !self.refresh_current().await? {}
+        Ok(Arc::clone(&self.entry().obj))
+    }
+    /// Same as [SharedCache::get_or_refresh()] but the value is returned as [CacheValue::Owned] so the code
+    /// can be shared with [crate::Object::get_or_refresh_value()].
+    pub async fn get_or_refresh_value<'a>(&self) -> Result<CacheValue<'a, T>, E> where T: 'a {
+        self.get_or_refresh().await.map(CacheValue::Owned)
+    }
+    /// Return a future that resolve once the next refresh of any clone, including a background one, success.
+    /// Only refresh that complete after this call count, even if the future isn't polled yet.
+    /// It is lighter than [crate::WatchedCache] when the new value itself isn't needed.
+    /// A failed refresh or one discarded by [SharedCache::invalidate()] doesn't resolve it.
+    ///
+    /// Require `tokio` feature.
+    #[cfg(feature = "tokio")]
+    pub fn notified_on_refresh(&self) -> impl Future<Output = ()> + '_ {
+        self.inner.refreshed.notified()
+    }
+    /// Wait until the background refresh in progress, if any, is done or cancelled by [SharedCache::invalidate()].
+    /// Return `false` immediately if there's no background refresh or it was already cancelled.
+    #[cfg(feature = "tokio")]
+    async fn wait_background_refresh(&self) -> bool {
+        let mut done = pin!(self.inner.background_done.notified());
+        done.as_mut().enable();
+        if !self.inner.background_refresh.load(Ordering::Acquire) || self.inner.background_generation.load(Ordering::Acquire) != self.inner.generation.load(Ordering::Acquire) {
+            return false
+        }
+        done.await;
+        true
+    }
+    /// Number of time `refresh_fn` was called by any clone of this cache, including the failed one.
+    pub fn refresh_count(&self) -> u64 {
+        self.inner.refresh_count.load(Ordering::Relaxed)
+    }
+    /// Get time remain that the cache still valid.
+    pub fn time_remain(&self) -> Duration {
+        self.inner.ttl.saturating_sub(clock::elapsed(self.entry().last_update))
+    }
+}
+/// Background refresh need the cache to be moved into a spawned task.
+///
+/// Require `tokio` feature.
+#[cfg(feature = "tokio")]
+impl<T, F, Fut, E> SharedCache<T, F, E> where T: Send + Sync + 'static, F: Fn() -> Fut + Send + Sync + 'static, Fut: Future<Output = Result<T, E>> + Send + 'static, E: 'static {
+    /// Serve expired value for `grace` more time after `ttl` while it is refreshed in background by
+    /// [SharedCache::get_or_refresh_graceful()]. The setting belong to this handle and clones made from it afterward.
+    pub fn with_grace(mut self, grace: Duration) -> Self {
+        self.grace = Some(grace);
+        self
+    }
+    /// Same as [SharedCache::get_or_refresh()] but within the grace period set by [SharedCache::with_grace()],
+    /// i.e. between `ttl` and `ttl + grace`, the expired value is returned immediately while a refresh is
+    /// spawned on tokio runtime. Only one background refresh run at a time and its error is discarded.
+    /// Beyond the grace period, the caller wait for the refresh.
+    pub async fn get_or_refresh_graceful(&self) -> Result<Arc<T>, E> {
+        let (obj, age) = {
+            let entry = self.entry();
+            (Arc::clone(&entry.obj), clock::elapsed(entry.last_update))
+        };
+        if age <= self.inner.ttl {
+            return Ok(obj)
+        }
+        if self.grace.is_some_and(|grace| age <= self.inner.ttl.saturating_add(grace)) {
+            self.prefetch();
+            return Ok(obj)
+        }
+        self.get_or_refresh().await
+    }
+    /// Start a refresh on tokio runtime and return immediately, e.g. to hint an upcoming read on a hot path.
+    /// Reader keep getting the previous value until the refresh is done. Its error is discarded.
+    /// If a background refresh is already in progress, even one cancelled by [SharedCache::invalidate()],
+    /// no other refresh is started. [SharedCache::get_or_refresh()] await the one in progress, unless it was
+    /// cancelled, instead of calling `refresh_fn` again.
+    pub fn prefetch(&self) {
+        if self.inner.background_refresh.load(Ordering::Acquire) {
+            return
+        }
+        self.inner.background_generation.store(self.inner.generation.load(Ordering::Acquire), Ordering::Release);
+        if self.inner.background_refresh.compare_exchange(false, true, Ordering::AcqRel, Ordering::Acquire).is_ok() {
+            let cache = self.clone();
+            tokio::spawn(async move {
+                let _done = BackgroundRefresh { inner: &*cache.inner };
+                let _ = cache.refresh().await;
+            });
+        }
+    }
+    /// Spawn a task on tokio runtime that refresh the cache every `interval`, so reader rarely see
+    /// an expired value. A failed refresh is discarded and retried on the next interval.
+    /// The task run until [AutoRefresh::shutdown()] is called or the returned [AutoRefresh] is dropped.
+    pub fn spawn_auto_refresh(&self, interval: Duration) -> AutoRefresh {
+        let cache = self.clone();
+        let stop = Arc::new(Notify::new());
+        let stopped = Arc::clone(&stop);
+        let task = tokio::spawn(async move {
+            while tokio::time::timeout(interval, stopped.notified()).await.is_err() {
+                let _ = cache.refresh().await;
+            }
+        });
+        AutoRefresh {
+            stop,
+            task: Some(task)
+        }
+    }
+}
+/// Handle of a task spawned by [SharedCache::spawn_auto_refresh()].
+///
+/// `Drop` can't wait for the task so dropping this handle abort the task, even in the middle of a refresh.
+/// Use [AutoRefresh::shutdown()] to stop it cleanly.
+///
+/// Require `tokio` feature.
+#[cfg(feature = "tokio")]
+pub struct AutoRefresh {
+    stop: Arc<Notify>,
+    task: Option<JoinHandle<()>>
+}
+#[cfg(feature = "tokio")]
+impl AutoRefresh {
+    /// Signal the task to stop then wait until it is done. A refresh in progress is completed first.
+    pub async fn shutdown(mut self) {
+        self.stop.notify_one();
+        if let Some(task) = self.task.take() {
+            let _ = task.await;
+        }
+    }
+}
+#[cfg(feature = "tokio")]
+impl Drop for AutoRefresh {
+    fn drop(&mut self) {
+        if let Some(task) = &self.task {
+            task.abort();
+        }
+    }
+}
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+    use std::time::Duration;
+
+    use tokio::time::sleep;
+
+    use super::*;
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn prefetch_panic() {
+        let calls = Arc::new(AtomicU32::new(0));
+        let counter = Arc::clone(&calls);
+        let cached = SharedCache::new(Duration::ZERO, 100, move || {
+            let call = counter.fetch_add(1, Ordering::Relaxed);
+            async move {
+                tokio::task::yield_now().await;
+                if call == 0 {
+                    panic!("backend crashed")
+                }
+                Ok::<u16, ()>(200)
+            }
+        });
+        cached.prefetch();
+        let value = tokio::time::timeout(Duration::from_secs(1), cached.get_or_refresh()).await.expect("Expect get_or_refresh to not hang after a panicked prefetch");
+        assert_eq!(*value.unwrap(), 200, "Expect caller to refresh by itself");
+        let refreshed = cached.notified_on_refresh();
+        cached.prefetch();
+        tokio::time::timeout(Duration::from_secs(1), refreshed).await.expect("Expect another prefetch to start after a panicked one");
+    }
+    #[tokio::test]
+    async fn shared_refresh() {
+        let cached = SharedCache::new(Duration::from_secs(1), 100, || async {Ok::<u16, ()>(200)});
+        let other = cached.clone();
+        assert_eq!(*cached.get().unwrap(), 100, "Expect {} to equals {}", *cached.get().unwrap(), 100);
+        other.refresh().await.unwrap();
+        assert_eq!(*cached.get().unwrap(), 200, "Expect refresh on a clone to be visible on the original");
+        assert_eq!(cached.refresh_count(), 1, "Expect refresh count to be shared between clones");
+    }
+    #[tokio::test]
+    async fn panic_in_refresh() {
+        let cached = SharedCache::new(Duration::from_secs(60), 100, || async {
+            if true {
+                panic!("backend exploded");
+            }
+            Ok::<u16, ()>(200)
+        });
+        let result = cached.refresh_catching_panic().await;
+        assert!(matches!(&result, Err(RefreshError::Panicked(message)) if message == "backend exploded"), "Expect descriptive panic error but got {:?}", result);
+        assert_eq!(*cached.get().unwrap(), 100, "Expect previous value to be kept after panic");
+        assert_eq!(*cached.clone().get_or_refresh().await.unwrap(), 100, "Expect cache to stay usable after panic");
+    }
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn grace_period() {
+        let calls = Arc::new(AtomicU32::new(0));
+        let counter = Arc::clone(&calls);
+        let cached = SharedCache::new(Duration::from_millis(50), 0, move || {
+            let counter = Arc::clone(&counter);
+            async move {
+                sleep(Duration::from_millis(20)).await;
+                Ok::<u32, ()>(counter.fetch_add(1, Ordering::SeqCst) + 1)
+            }
+        }).with_grace(Duration::from_millis(100));
+        assert_eq!(*cached.get_or_refresh_graceful().await.unwrap(), 0, "Expect valid value within ttl");
+        sleep(Duration::from_millis(60)).await;
+        assert_eq!(*cached.get_or_refresh_graceful().await.unwrap(), 0, "Expect stale value immediately within grace");
+        assert_eq!(*cached.get_or_refresh_graceful().await.unwrap(), 0, "Expect no second background refresh");
+        sleep(Duration::from_millis(40)).await;
+        assert_eq!(*cached.get().unwrap(), 1, "Expect background refresh to update the value");
+        sleep(Duration::from_millis(200)).await;
+        assert_eq!(*cached.get_or_refresh_graceful().await.unwrap(), 2, "Expect caller to wait for refresh beyond grace");
+        assert_eq!(calls.load(Ordering::SeqCst), 2, "Expect {} to equals {}", calls.load(Ordering::SeqCst), 2);
+    }
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn join_background_refresh() {
+        let calls = Arc::new(AtomicU32::new(0));
+        let counter = Arc::clone(&calls);
+        let cached = SharedCache::new(Duration::from_millis(20), 0, move || {
+            let counter = Arc::clone(&counter);
+            async move {
+                sleep(Duration::from_millis(20)).await;
+                Ok::<u32, ()>(counter.fetch_add(1, Ordering::SeqCst) + 1)
+            }
+        }).with_grace(Duration::from_secs(1));
+        sleep(Duration::from_millis(30)).await;
+        assert_eq!(*cached.get_or_refresh_graceful().await.unwrap(), 0, "Expect stale value while background refresh start");
+        assert_eq!(*cached.get_or_refresh().await.unwrap(), 1, "Expect manual refresh to await the background one");
+        assert_eq!(calls.load(Ordering::SeqCst), 1, "Expect {} to equals {}", calls.load(Ordering::SeqCst), 1);
+    }
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn shutdown_auto_refresh() {
+        let cached = SharedCache::new(Duration::from_secs(60), 0, || async {Ok::<u16, ()>(200)});
+        let auto_refresh = cached.spawn_auto_refresh(Duration::from_millis(10));
+        sleep(Duration::from_millis(35)).await;
+        auto_refresh.shutdown().await;
+        let count = cached.refresh_count();
+        assert!(count >= 1, "Expect task to refresh periodically but got {} refresh", count);
+        sleep(Duration::from_millis(30)).await;
+        assert_eq!(cached.refresh_count(), count, "Expect no refresh after shutdown");
+        assert_eq!(*cached.get().unwrap(), 200, "Expect {} to equals {}", *cached.get().unwrap(), 200);
+    }
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn read_during_refresh() {
+        let generation = Arc::new(AtomicU32::new(0));
+        let counter = Arc::clone(&generation);
+        let cached = SharedCache::new(Duration::from_secs(60), vec![0u32; 1024], move || {
+            let counter = Arc::clone(&counter);
+            async move {
+                sleep(Duration::from_millis(50)).await;
+                let n = counter.fetch_add(1, Ordering::SeqCst) + 1;
+                Ok::<Vec<u32>, ()>(vec![n; 1024])
+            }
+        });
+        let done = Arc::new(AtomicBool::new(false));
+        let readers: Vec<_> = (0..4).map(|_| {
+            let cached = cached.clone();
+            let done = Arc::clone(&done);
+            tokio::spawn(async move {
+                let mut last = 0;
+                while !done.load(Ordering::SeqCst) {
+                    let value = cached.get().unwrap();
+                    assert!(value.iter().all(|v| *v == value[0]), "Reader should never see a partially updated value");
+                    assert!(value[0] >= last, "Reader should never go back to an older value");
+                    last = value[0];
+                    tokio::task::yield_now().await;
+                }
+            })
+        }).collect();
+        let refresher = {
+            let cached = cached.clone();
+            tokio::spawn(async move {
+                for _ in 0..3 {
+                    cached.refresh().await.unwrap();
+                }
+            })
+        };
+        sleep(Duration::from_millis(10)).await;
+        assert_eq!(cached.get().unwrap()[0], 0, "Reader should get the previous value while refresh is running");
+        refresher.await.unwrap();
+        done.store(true, Ordering::SeqCst);
+        for reader in readers {
+            reader.await.unwrap();
+        }
+        assert_eq!(cached.get().unwrap()[0], 3, "Expect the last refreshed value once all refresh are done");
+    }
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn prefetch_in_background() {
+        let cached = SharedCache::new(Duration::from_secs(60), 100, || async {
+            sleep(Duration::from_millis(20)).await;
+            Ok::<u16, ()>(200)
+        });
+        cached.prefetch();
+        assert_eq!(*cached.get().unwrap(), 100, "Expect previous value right after prefetch");
+        while *cached.get().unwrap() != 200 {
+            sleep(Duration::from_millis(5)).await;
+        }
+        assert_eq!(cached.refresh_count(), 1, "Expect {} to equals {}", cached.refresh_count(), 1);
+    }
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn cancel_refresh_on_invalidate() {
+        let calls = Arc::new(AtomicU32::new(0));
+        let counter = Arc::clone(&calls);
+        let cached = SharedCache::new(Duration::from_secs(60), 0, move || {
+            let counter = Arc::clone(&counter);
+            async move {
+                let n = counter.fetch_add(1, Ordering::SeqCst) + 1;
+                sleep(Duration::from_millis(40)).await;
+                Ok::<u32, ()>(n)
+            }
+        });
+        cached.prefetch();
+        sleep(Duration::from_millis(10)).await;
+        cached.invalidate(true);
+        assert_eq!(*cached.get_or_refresh().await.unwrap(), 2, "Expect a fresh refresh instead of waiting for the cancelled one");
+        sleep(Duration::from_millis(50)).await;
+        assert_eq!(*cached.get().unwrap(), 2, "Expect result of the cancelled refresh to be discarded");
+        cached.prefetch();
+        sleep(Duration::from_millis(10)).await;
+        cached.invalidate(false);
+        sleep(Duration::from_millis(40)).await;
+        assert_eq!(*cached.get().unwrap(), 3, "Expect refresh to be stored when it isn't cancelled");
+    }
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn notify_on_refresh() {
+        let cached = SharedCache::new(Duration::from_secs(60), 100, || async {Ok::<u16, ()>(200)});
+        let refreshed = cached.notified_on_refresh();
+        let other = cached.clone();
+        let refresher = tokio::spawn(async move {
+            sleep(Duration::from_millis(20)).await;
+            other.refresh().await.unwrap();
+        });
+        tokio::time::timeout(Duration::from_secs(1), refreshed).await.expect("Expect waiter to be notified by the refresh of another task");
+        assert_eq!(*cached.get().unwrap(), 200, "Expect refreshed value once notified");
+        refresher.await.unwrap();
+    }
+}
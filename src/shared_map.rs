@@ -0,0 +1,178 @@
+//! A keyed cache that can be shared between threads and tasks.
+use std::collections::HashMap;
+use std::future::{poll_fn, Future};
+use std::hash::Hash;
+use std::marker::PhantomData;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::task::{Poll, Waker};
+use std::time::{Duration, SystemTime};
+
+use crate::clock;
+
+struct State<K, V> {
+    entries: HashMap<K, (Arc<V>, SystemTime)>,
+    in_flight: HashMap<K, Vec<Waker>>
+}
+struct Inner<K, V, F> {
+    ttl: Duration,
+    state: Mutex<State<K, V>>,
+    refresh_fn: F,
+    refresh_count: AtomicU64
+}
+impl<K, V, F> Inner<K, V, F> {
+    fn state(&self) -> MutexGuard<'_, State<K, V>> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// Mark `key` as being refreshed. When it is dropped, even if the refresh was cancelled or panicked,
+/// the mark is removed and every waiting caller is woken up.
+struct InFlight<'a, K, V, F> where K: Eq + Hash {
+    inner: &'a Inner<K, V, F>,
+    key: &'a K
+}
+impl<K, V, F> Drop for InFlight<'_, K, V, F> where K: Eq + Hash {
+    fn drop(&mut self) {
+        let waiters = self.inner.state().in_flight.remove(self.key).unwrap_or_default();
+        waiters.into_iter().for_each(Waker::wake);
+    }
+}
+
+/// Thread safe keyed cache which can be cloned and shared between threads and tasks.
+/// Every clone point to the same cached values.
+///
+/// Unlike [crate::CacheMap], every method only take `&self` and `refresh_fn` load one key at a time.
+/// Concurrent [SharedCacheMap::get_or_refresh()] of the same missing or expired key call `refresh_fn`
+/// only once. Every other caller wait for that refresh and get its value. Different keys are refreshed
+/// independently and concurrently. If the refresh fail, a waiting caller refresh the key by itself.
+///
+/// The waiting doesn't depend on any async runtime.
+/// ```rust
+/// use std::time::Duration;
+/// use generic_cache::SharedCacheMap;
+///
+/// # tokio_test::block_on(async {
+/// let cached = SharedCacheMap::new(Duration::from_secs(1), |key: u16| async move {Ok::<u16, ()>(key * 2)});
+/// assert_eq!(*cached.get_or_refresh(&2).await.unwrap(), 4);
+/// assert_eq!(*cached.get(&2).unwrap(), 4);
+/// # })
+/// ```
+pub struct SharedCacheMap<K, V, F, E = ()> {
+    inner: Arc<Inner<K, V, F>>,
+    _error: PhantomData<fn() -> E>
+}
+impl<K, V, F, E> Clone for SharedCacheMap<K, V, F, E> {
+    fn clone(&self) -> Self {
+        SharedCacheMap {
+            inner: Arc::clone(&self.inner),
+            _error: PhantomData
+        }
+    }
+}
+impl<K, V, F, Fut, E> SharedCacheMap<K, V, F, E> where K: Eq + Hash + Clone, F: Fn(K) -> Fut, Fut: Future<Output = Result<V, E>> {
+    /// Create a new empty shared keyed cache.
+    /// `ttl` is "time to live" which is duration that each cached value will be return.
+    /// `refresh_fn` is a function to load the value of a key.
+    pub fn new(ttl: Duration, refresh_fn: F) -> SharedCacheMap<K, V, F, E> {
+        SharedCacheMap {
+            inner: Arc::new(Inner {
+                ttl,
+                state: Mutex::new(State {
+                    entries: HashMap::new(),
+                    in_flight: HashMap::new()
+                }),
+                refresh_fn,
+                refresh_count: AtomicU64::new(0)
+            }),
+            _error: PhantomData
+        }
+    }
+    /// Read cached value of `key`. Return `None` if `key` isn't cached or it is already expired.
+    pub fn get(&self, key: &K) -> Option<Arc<V>> {
+        let state = self.inner.state();
+        state.entries.get(key).filter(|(_, last_update)| clock::elapsed(*last_update) <= self.inner.ttl).map(|(obj, _)| Arc::clone(obj))
+    }
+    /// Read cached value of `key` or refresh it if it is missing or expired then return the new value.
+    /// If the same key is already being refreshed by another caller, wait for that refresh instead.
+    pub async fn get_or_refresh(&self, key: &K) -> Result<Arc<V>, E> {
+        loop {
+            if let Some(obj) = self.get(key) {
+                return Ok(obj)
+            }
+            let refreshing = {
+                let mut state = self.inner.state();
+                let refreshing = state.in_flight.contains_key(key);
+                if !refreshing {
+                    state.in_flight.insert(key.clone(), Vec::new());
+                }
+                refreshing
+            };
+            if !refreshing {
+                break
+            }
+            poll_fn(|cx| {
+                let mut state = self.inner.state();
+                match state.in_flight.get_mut(key) {
+                    Some(waiters) => {
+                        if !waiters.iter().any(|waker| waker.will_wake(cx.waker())) {
+                            waiters.push(cx.waker().clone());
+                        }
+                        Poll::Pending
+                    },
+                    None => Poll::Ready(())
+                }
+            }).await;
+        }
+        let _in_flight = InFlight { inner: &*self.inner, key };
+        self.inner.refresh_count.fetch_add(1, Ordering::Relaxed);
+        let obj = Arc::new((self.inner.refresh_fn)(key.clone()).await?);
+        self.inner.state().entries.insert(key.clone(), (Arc::clone(&obj), clock::now()));
+        Ok(obj)
+    }
+    /// Number of time `refresh_fn` was called by any clone of this cache, including the failed one.
+    pub fn refresh_count(&self) -> u64 {
+        self.inner.refresh_count.load(Ordering::Relaxed)
+    }
+}
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicU32;
+
+    use tokio::time::sleep;
+
+    use super::*;
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn single_flight_per_key() {
+        let running = Arc::new(AtomicU32::new(0));
+        let max_running = Arc::new(AtomicU32::new(0));
+        let (current, max) = (Arc::clone(&running), Arc::clone(&max_running));
+        let cached = SharedCacheMap::new(Duration::from_secs(60), move |key: u16| {
+            let (current, max) = (Arc::clone(&current), Arc::clone(&max));
+            async move {
+                max.fetch_max(current.fetch_add(1, Ordering::SeqCst) + 1, Ordering::SeqCst);
+                sleep(Duration::from_millis(50)).await;
+                current.fetch_sub(1, Ordering::SeqCst);
+                Ok::<u16, ()>(key * 10)
+            }
+        });
+        let tasks: Vec<_> = (0..8).map(|_| {
+            let cached = cached.clone();
+            tokio::spawn(async move { *cached.get_or_refresh(&1).await.unwrap() })
+        }).collect();
+        for task in tasks {
+            assert_eq!(task.await.unwrap(), 10, "Expect every caller to get the refreshed value");
+        }
+        assert_eq!(cached.refresh_count(), 1, "Expect one refresh for one key but got {}", cached.refresh_count());
+        let tasks: Vec<_> = (0..8).map(|i| {
+            let cached = cached.clone();
+            tokio::spawn(async move { *cached.get_or_refresh(&(2 + i % 2)).await.unwrap() })
+        }).collect();
+        for task in tasks {
+            task.await.unwrap();
+        }
+        assert_eq!(cached.refresh_count(), 3, "Expect one more refresh per key but got {}", cached.refresh_count());
+        assert_eq!(max_running.load(Ordering::SeqCst), 2, "Expect different keys to be refreshed concurrently");
+    }
+}
@@ -0,0 +1,40 @@
+//! A borrow of a cached value that extend its life once it is no longer used.
+use std::ops::Deref;
+
+use crate::Object;
+
+/// Value returned by [Object::get_or_refresh_sliding()]. It dereference to the cached value and when it is
+/// dropped, it call [Object::touch()] so the value stay valid for full `ttl` from its last use.
+/// This give a sliding expiry where a value that keep being used is never refreshed on expiry.
+/// ```rust
+/// use std::time::Duration;
+/// use generic_cache::Object;
+///
+/// # tokio_test::block_on(async {
+/// let mut cached = Object::new(Duration::from_secs(1), 100, async || {Ok::<u16, ()>(200)});
+/// let value = cached.get_or_refresh_sliding().await.unwrap();
+/// assert_eq!(*value, 100);
+/// drop(value); // the value is valid for 1 more second from now
+/// # })
+/// ```
+pub struct Sliding<'a, T, F, E> {
+    cache: &'a mut Object<T, F, E>
+}
+impl<'a, T, F, E> Sliding<'a, T, F, E> {
+    pub(crate) fn new(cache: &'a mut Object<T, F, E>) -> Sliding<'a, T, F, E> {
+        Sliding {
+            cache
+        }
+    }
+}
+impl<T, F, E> Deref for Sliding<'_, T, F, E> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        &self.cache.obj
+    }
+}
+impl<T, F, E> Drop for Sliding<'_, T, F, E> {
+    fn drop(&mut self) {
+        self.cache.touch();
+    }
+}
@@ -0,0 +1,37 @@
+//! Turn a cached object into a [Stream] of refreshed values.
+use std::future::Future;
+use std::marker::PhantomData;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures_core::Stream;
+
+/// Endless stream that repeatedly run the future created by `step` from the state returned by the previous future.
+pub(crate) struct Unfold<S, G, Fut, Item> {
+    step: G,
+    pending: Pin<Box<Fut>>,
+    _marker: PhantomData<fn(S) -> Item>
+}
+impl<S, G, Fut, Item> Unfold<S, G, Fut, Item> where G: FnMut(S) -> Fut, Fut: Future<Output = (Item, S)> {
+    pub(crate) fn new(state: S, mut step: G) -> Unfold<S, G, Fut, Item> {
+        let pending = Box::pin(step(state));
+        Unfold {
+            step,
+            pending,
+            _marker: PhantomData
+        }
+    }
+}
+impl<S, G, Fut, Item> Stream for Unfold<S, G, Fut, Item> where G: FnMut(S) -> Fut + Unpin, Fut: Future<Output = (Item, S)> {
+    type Item = Item;
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Item>> {
+        let this = self.get_mut();
+        match this.pending.as_mut().poll(cx) {
+            Poll::Ready((item, state)) => {
+                this.pending = Box::pin((this.step)(state));
+                Poll::Ready(Some(item))
+            },
+            Poll::Pending => Poll::Pending
+        }
+    }
+}
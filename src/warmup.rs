@@ -0,0 +1,84 @@
+//! Build many cached objects at startup and refresh them together.
+use std::future::{poll_fn, Future};
+use std::pin::Pin;
+use std::task::Poll;
+use std::time::Duration;
+
+use crate::Object;
+
+/// Create an [Object] for every `(ttl, refresh_fn)` in `specs` the same way as [Object::new_and_refresh()]
+/// but the initial refreshes run concurrently with at most `limit` of them running at the same time.
+/// This shorten startup of a service that has many caches without flooding the backend.
+/// It doesn't need any async runtime. A `limit` of 0 is treated as 1.
+///
+/// The result is in the same order as `specs`. If any refresh fail, its error is returned and
+/// the other refreshes are dropped.
+/// ```rust
+/// use std::time::Duration;
+/// use generic_cache::warmup_concurrent;
+///
+/// # tokio_test::block_on(async {
+/// let specs = (1..=3).map(|i| (Duration::from_secs(60), move || async move {Ok::<u16, ()>(i * 100)}));
+/// let caches = warmup_concurrent(specs, 2).await.unwrap();
+/// assert_eq!(*caches[2].get().unwrap(), 300);
+/// # })
+/// ```
+pub async fn warmup_concurrent<T, F, E>(specs: impl IntoIterator<Item = (Duration, F)>, limit: usize) -> Result<Vec<Object<T, F, E>>, E> where F: AsyncFnMut() -> Result<T, E> {
+    let limit = limit.max(1);
+    let mut pending = specs.into_iter().enumerate();
+    let mut running: Vec<(usize, Pin<Box<_>>)> = Vec::new();
+    let mut done = Vec::new();
+    poll_fn(|cx| loop {
+        while running.len() < limit {
+            let Some((i, (ttl, refresh_fn))) = pending.next() else {
+                break
+            };
+            running.push((i, Box::pin(Object::new_and_refresh(ttl, refresh_fn))));
+        }
+        if running.is_empty() {
+            return Poll::Ready(Ok(()))
+        }
+        let before = running.len();
+        let mut k = 0;
+        while k < running.len() {
+            match running[k].1.as_mut().poll(cx) {
+                Poll::Ready(Ok(obj)) => done.push((running.swap_remove(k).0, obj)),
+                Poll::Ready(Err(e)) => return Poll::Ready(Err(e)),
+                Poll::Pending => k += 1
+            }
+        }
+        if running.len() == before {
+            return Poll::Pending
+        }
+    }).await?;
+    done.sort_by_key(|(i, _)| *i);
+    Ok(done.into_iter().map(|(_, obj)| obj).collect())
+}
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use tokio::time::sleep;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn concurrent_warmup() {
+        let in_flight = Cell::new(0);
+        let peak = Cell::new(0);
+        let specs = (0..5u16).map(|i| {
+            let (in_flight, peak) = (&in_flight, &peak);
+            (Duration::from_secs(60), async move || {
+                in_flight.set(in_flight.get() + 1);
+                peak.set(peak.get().max(in_flight.get()));
+                sleep(Duration::from_millis(10)).await;
+                in_flight.set(in_flight.get() - 1);
+                Ok::<u16, ()>(i)
+            })
+        });
+        let caches = warmup_concurrent(specs, 2).await.unwrap();
+        let values: Vec<u16> = caches.iter().map(|cached| *cached.get().unwrap()).collect();
+        assert_eq!(values, vec![0, 1, 2, 3, 4], "Expect every cache to be populated in the given order");
+        assert_eq!(peak.get(), 2, "Expect refreshes to run concurrently but never more than the limit");
+    }
+}
@@ -0,0 +1,121 @@
+//! A cached object that push every new value to its subscribers.
+use tokio::sync::watch;
+
+use crate::{Object, TimeoutError};
+
+/// Wrapper of [Object] which send every new value to a [tokio::sync::watch] channel.
+/// Subscriber get notified whenever the value is refreshed or set manually instead of polling the cache.
+/// A failed refresh or a refresh that return a value equal to the cached one, see [Object::refresh_if_changed()],
+/// doesn't notify anyone.
+///
+/// Require `tokio` feature.
+/// ```rust
+/// use std::time::Duration;
+/// use generic_cache::{Object, WatchedCache};
+///
+/// # tokio_test::block_on(async {
+/// let mut cached = WatchedCache::new(Object::new(Duration::from_secs(1), 100, async || {Ok::<u16, ()>(200)}));
+/// let mut receiver = cached.subscribe();
+/// cached.refresh().await.unwrap();
+/// receiver.changed().await.unwrap();
+/// assert_eq!(*receiver.borrow(), 200);
+/// # })
+/// ```
+pub struct WatchedCache<T, F, E = ()> {
+    inner: Object<T, F, E>,
+    sender: watch::Sender<T>
+}
+impl<T, F, E> WatchedCache<T, F, E> where T: Clone, F: AsyncFnMut() -> Result<T, E> {
+    /// Wrap `inner` so its new values are sent to subscribers. The current value is the initial value of the channel.
+    pub fn new(inner: Object<T, F, E>) -> WatchedCache<T, F, E> {
+        let (sender, _) = watch::channel(inner.obj.clone());
+        WatchedCache {
+            inner,
+            sender
+        }
+    }
+    /// Create a receiver that see the current value and get notified on every new value.
+    pub fn subscribe(&self) -> watch::Receiver<T> {
+        self.sender.subscribe()
+    }
+    /// Number of receivers currently subscribed, e.g. to detect leaked receivers or to skip refreshing
+    /// when nobody is listening.
+    pub fn watchers_count(&self) -> usize {
+        self.sender.receiver_count()
+    }
+    fn notify(&self) {
+        self.sender.send_replace(self.inner.obj.clone());
+    }
+    /// Refresh cache immediately then notify subscribers if refresh success and the value changed.
+    pub async fn refresh(&mut self) -> Result<(), E> where T: PartialEq {
+        if self.inner.refresh_if_changed().await? {
+            self.notify();
+        }
+        Ok(())
+    }
+    /// Read current cached value or return Error if cache is already expired.
+    pub fn get(&self) -> Result<&T, TimeoutError> {
+        self.inner.get()
+    }
+    /// Read current cached value or refresh the value if it is already expired then
+    /// return the new value. Subscribers are notified only if a refresh happened and the value changed.
+    pub async fn get_or_refresh(&mut self) -> Result<&T, E> where T: PartialEq {
+        if self.inner.lookup().is_some() && self.inner.refresh_if_changed().await? {
+            self.notify();
+        }
+        Ok(&self.inner.obj)
+    }
+    /// Store `value` as with [Object::set()], which reset the `ttl`, and send it to subscribers in one operation.
+    pub fn set_and_notify(&mut self, value: T) {
+        self.inner.set(value);
+        self.notify();
+    }
+    /// Consume this wrapper and return the wrapped [Object]. Receivers see the channel as closed.
+    pub fn into_inner(self) -> Object<T, F, E> {
+        self.inner
+    }
+}
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn set_and_notify_subscriber() {
+        let mut cached = WatchedCache::new(Object::new(Duration::ZERO, 100, async || {Ok::<u16, ()>(200)}));
+        let mut receiver = cached.subscribe();
+        let waiter = tokio::spawn(async move {
+            receiver.changed().await.unwrap();
+            *receiver.borrow_and_update()
+        });
+        cached.set_and_notify(300);
+        assert_eq!(waiter.await.unwrap(), 300, "Expect subscriber to receive the manually set value");
+        assert_eq!(cached.into_inner().refresh_count(), 0, "Expect set to not call refresh_fn");
+    }
+    #[tokio::test]
+    async fn skip_unchanged_value() {
+        let mut count = 0u16;
+        let mut cached = WatchedCache::new(Object::new(Duration::ZERO, 100, async move || {
+            count += 1;
+            Ok::<u16, ()>(if count < 3 { 100 } else { 200 })
+        }));
+        let mut receiver = cached.subscribe();
+        cached.refresh().await.unwrap();
+        cached.get_or_refresh().await.unwrap();
+        assert!(!receiver.has_changed().unwrap(), "Expect watcher to not be notified of an identical value");
+        cached.refresh().await.unwrap();
+        assert!(receiver.has_changed().unwrap(), "Expect watcher to be notified of a different value");
+        assert_eq!(*receiver.borrow_and_update(), 200, "Expect watcher to see the new value");
+    }
+    #[test]
+    fn count_watchers() {
+        let cached = WatchedCache::new(Object::new(Duration::ZERO, 100, async || {Ok::<u16, ()>(200)}));
+        assert_eq!(cached.watchers_count(), 0, "Expect no watcher before subscribe");
+        let first = cached.subscribe();
+        let _second = cached.subscribe();
+        assert_eq!(cached.watchers_count(), 2, "Expect {} to equals {}", cached.watchers_count(), 2);
+        drop(first);
+        assert_eq!(cached.watchers_count(), 1, "Expect dropped receiver to not be counted");
+    }
+}